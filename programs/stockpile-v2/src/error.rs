@@ -73,4 +73,187 @@ pub enum ProtocolError {
 
     #[msg("This project has already claimed their grant.")]
     AlreadyClaimed,
-}
\ No newline at end of file
+
+    #[msg("This funding source does not belong to this pool.")]
+    SourceMismatch,
+
+    #[msg("This deposit exceeds the amount committed by the funding source.")]
+    SourceCommitmentExceeded,
+
+    #[msg("Earmarks must list between 1 and 10 participants.")]
+    InvalidEarmark,
+
+    #[msg("There are no funds to refund to this funding source.")]
+    NothingToRefund,
+
+    #[msg("Matching caps are invalid. Share caps must be between 0 and 1.")]
+    InvalidMatchingCaps,
+
+    #[msg("This pool has already started.")]
+    PoolAlreadyStarted,
+
+    #[msg("This category does not exist in this pool.")]
+    InvalidCategory,
+
+    #[msg("Subscriptions need a non-zero amount and a period of at least a day.")]
+    InvalidSubscription,
+
+    #[msg("This subscription is paused.")]
+    SubscriptionPaused,
+
+    #[msg("This subscription has been cancelled.")]
+    SubscriptionCancelled,
+
+    #[msg("This subscription's next payment isn't due yet.")]
+    SubscriptionNotDue,
+
+    #[msg("This project's campaign has ended.")]
+    CampaignEnded,
+
+    #[msg("This project's campaign is still running.")]
+    CampaignStillActive,
+
+    #[msg("This project did not meet its goal.")]
+    GoalNotMet,
+
+    #[msg("This project met its goal, contributions can't be refunded.")]
+    GoalMet,
+
+    #[msg("Contributions to this project are not refundable.")]
+    NotRefundable,
+
+    #[msg("All-or-nothing campaigns can't participate in funding rounds.")]
+    AllOrNothingCampaign,
+
+    #[msg("A project's goal and campaign can't be changed once it has raised funds.")]
+    CampaignLocked,
+
+    #[msg("The price confidence interval is too wide")]
+    PythPriceConfidenceTooWide,
+
+    #[msg("Oracle config is invalid")]
+    InvalidOracleConfig,
+
+    #[msg("None of the oracle sources for this mint returned a valid price")]
+    OraclePriceUnavailable,
+
+    #[msg("The project does not hold enough of this mint.")]
+    InsufficientBalance,
+
+    #[msg("This pool can't move to the requested state.")]
+    InvalidStateTransition,
+
+    #[msg("This pool's round hasn't ended yet.")]
+    PoolNotEnded,
+
+    #[msg("This pool hasn't been finalized yet.")]
+    PoolNotFinalized,
+
+    #[msg("This pool hasn't been cancelled.")]
+    PoolNotCancelled,
+
+    #[msg("This project hasn't enabled vote refunds.")]
+    VoteRefundsDisabled,
+
+    #[msg("A pool must run between the minimum and maximum durations.")]
+    PoolInvalidDuration,
+
+    #[msg("This pool's round isn't active.")]
+    PoolNotActive,
+
+    #[msg("Soft close config is invalid.")]
+    InvalidSoftClose,
+
+    #[msg("This account is already on the current version.")]
+    AccountAlreadyMigrated,
+
+    #[msg("This account doesn't match any known layout.")]
+    AccountNotMigratable,
+
+    #[msg("This account still holds funds.")]
+    AccountHoldsFunds,
+
+    #[msg("This pool hasn't been distributed or cancelled.")]
+    PoolNotSettled,
+
+    #[msg("This source still has funds in the pool.")]
+    SourceNotSettled,
+
+    #[msg("An account that paid rent into this pool is missing.")]
+    RentPayerMissing,
+
+    #[msg("The protocol is paused.")]
+    ProtocolPaused,
+
+    #[msg("This feature is paused.")]
+    FeaturePaused,
+
+    #[msg("Too many admins.")]
+    TooManyAdmins,
+
+    #[msg("Protocol limits are invalid.")]
+    InvalidProtocolLimits,
+
+    #[msg("Fees exceed the protocol's cap.")]
+    FeeTooHigh,
+
+    #[msg("Too many fee exemptions.")]
+    TooManyFeeExemptions,
+
+    #[msg("Treasury account is invalid.")]
+    InvalidTreasury,
+
+    #[msg("Treasury account is required to pay the protocol fee.")]
+    TreasuryAccountMissing,
+
+    #[msg("Id counters can only move forward.")]
+    InvalidIdCounter,
+
+    #[msg("URI is too long.")]
+    UriTooLong,
+
+    #[msg("This directory page is full.")]
+    DirectoryPageFull,
+
+    #[msg("Batch is empty, too large, or doesn't match the accounts passed in.")]
+    InvalidBatch,
+
+    #[msg("The claim deadline has passed.")]
+    ClaimExpired,
+
+    #[msg("This pool has no claim deadline.")]
+    NoClaimDeadline,
+
+    #[msg("The claim deadline hasn't passed yet.")]
+    ClaimDeadlineNotPassed,
+
+    #[msg("Unclaimed payout policy is invalid.")]
+    InvalidUnclaimedPolicy,
+
+    #[msg("The pool unclaimed payouts roll over to is missing or doesn't match.")]
+    MissingRolloverPool,
+
+    #[msg("Round series template or schedule is invalid.")]
+    InvalidSeries,
+
+    #[msg("This series has spawned all of its rounds.")]
+    SeriesFinished,
+
+    #[msg("Pool isn't the expected round of this series.")]
+    SeriesPoolMismatch,
+
+    #[msg("Mint has a Token-2022 extension that isn't allowed.")]
+    MintExtensionNotAllowed,
+
+    #[msg("A token account for one of the pool's mints is missing.")]
+    PoolVaultMissing,
+
+    #[msg("An oracle account for one of the mint's price sources is missing.")]
+    OracleAccountMissing,
+
+    #[msg("Project is still in a pool that hasn't paid out or been cancelled.")]
+    ProjectPoolsUnsettled,
+
+    #[msg("A token account for one of the project's mints is missing.")]
+    ProjectVaultMissing,
+}
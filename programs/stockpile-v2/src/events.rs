@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+use crate::state::pool::MintAmount;
+
+/// Emitted when a project is created, with the id it was allocated
+#[event]
+pub struct ProjectCreatedEvent {
+    pub project: Pubkey,
+    pub project_id: u64,
+    pub creator: Pubkey,
+}
+
+/// Emitted when a pool is created, with the id it was allocated
+#[event]
+pub struct PoolCreatedEvent {
+    pub pool: Pubkey,
+    pub pool_id: u64,
+    pub creator: Pubkey,
+}
+
+/// Emitted when a project's or pool's metadata is updated
+#[event]
+pub struct MetadataUpdatedEvent {
+    pub account: Pubkey,
+    pub uri: String,
+    pub hash: [u8; 32],
+    pub revision: u32,
+}
+
+/// Emitted for every contribution, with the fees taken from it.
+/// `amount` is what the project received.
+#[event]
+pub struct ContributionEvent {
+    pub project: Pubkey,
+    pub pool: Option<Pubkey>,
+    pub donor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub frontend_fee: u64,
+    pub frontend: Option<Pubkey>,
+}
+
+/// Emitted when a project claims its matching payout,
+/// with the fees taken from it
+#[event]
+pub struct PayoutClaimedEvent {
+    pub pool: Pubkey,
+    pub project: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub protocol_fee: u64,
+    pub frontend_fee: u64,
+    pub frontend: Option<Pubkey>,
+}
+
+/// Emitted when payouts left unclaimed after the deadline are forfeited
+#[event]
+pub struct ClaimsExpiredEvent {
+    pub pool: Pubkey,
+    /// Forfeited payouts in each mint the pool was funded in
+    pub forfeited: Vec<MintAmount>,
+    /// Set when the forfeited payouts were rolled into another pool
+    pub rolled_over_to: Option<Pubkey>,
+}
+
+/// Emitted when a late vote extends a round's end date
+#[event]
+pub struct SoftCloseExtendedEvent {
+    pub pool: Pubkey,
+    pub new_end: u64,
+    pub extended_by: u64,
+}
+
+/// Receipt for a recurring payment made by a `Subscription`
+#[event]
+pub struct SubscriptionPaymentEvent {
+    pub subscription: Pubkey,
+    pub donor: Pubkey,
+    pub project: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub payment_number: u64,
+    pub timestamp: u64,
+}
+
+/// Emitted when a `RoundSeries` spawns its next round
+#[event]
+pub struct RoundSpawnedEvent {
+    pub series: Pubkey,
+    pub pool: Pubkey,
+    pub pool_id: u64,
+    pub round: u32,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Emitted when a series rolls unspent matching into its current round
+#[event]
+pub struct UnspentRolledOverEvent {
+    pub series: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
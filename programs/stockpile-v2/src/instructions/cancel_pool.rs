@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::*, pool::*}, error::ProtocolError};
+
+/// Cancels a pool that hasn't been finalized. Each funding
+/// source can then reclaim its full deposit through
+/// `withdraw_funds_from_round`, and projects can opt in
+/// to returning votes to their donors.
+pub fn cancel_pool(
+    ctx: Context<CancelPool>,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let payer = &ctx.accounts.payer;
+
+    require!(pool.admins.contains(&payer.key()), ProtocolError::NotAuthorized);
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    pool.cancel(current_time)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct CancelPool<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TokenAccount, TokenInterface};
+
+use crate::{state::{config::*, subscription::*}, error::ProtocolError};
+
+/// Cancels a `Subscription` and revokes its delegation
+/// over the donor's token account.
+pub fn cancel_subscription(ctx: Context<CancelSubscription>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Subscriptions)?;
+
+    let subscription = &mut ctx.accounts.subscription;
+
+    require!(
+        subscription.status != SubscriptionStatus::Cancelled,
+        ProtocolError::SubscriptionCancelled
+    );
+
+    subscription.status = SubscriptionStatus::Cancelled;
+
+    token_interface::revoke(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::Revoke {
+                source: ctx.accounts.payer_token_account.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Subscription::SEED_PREFIX.as_bytes(),
+            subscription.project.as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        mut,
+        token::mint = subscription.mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+
+use crate::state::{
+    config::*,
+    pool::*,
+    project::*,
+    registry::*,
+    series::*,
+};
+use crate::util::{set_and_maybe_realloc, set_pool_and_maybe_realloc};
+
+/// Enters a participant of a series' previous round into its current
+/// round, with a fresh share, if the series carries participants over.
+/// The project has to still be able to join a pool, and its category
+/// has to exist in the current round. Anyone can call this.
+pub fn carry_over_project(ctx: Context<CarryOverProject>, _series_id: u64) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let project_key = ctx.accounts.project.key();
+    let mut pool_data = ctx.accounts.pool.clone().into_inner();
+
+    require!(
+        ctx.accounts.series.template.carry_participants,
+        ProtocolError::MismatchedConfig
+    );
+
+    let category = ctx.accounts.previous_pool.project_shares
+        .iter()
+        .find(|p| p.project_key == project_key)
+        .ok_or(ProtocolError::NotInPool)?
+        .category;
+
+    ctx.accounts.project.can_join_pool()?;
+
+    pool_data.can_fund()?;
+
+    // Check to make sure the fundraiser isnt already in the pool
+    if pool_data.project_shares.iter().any(|p| p.project_key == project_key) {
+        return Err(ProtocolError::AlreadyEntered.into());
+    }
+
+    pool_data.add_participant(project_key, category)?;
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.pool,
+        pool_data,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // Record the entry, and index the pool under the project
+    let pool_key = ctx.accounts.pool.key();
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    ctx.accounts.entry.set_inner(
+        PoolEntry::new(
+            pool_key,
+            project_key,
+            category,
+            current_time,
+            *ctx.bumps
+                .get("entry")
+                .expect("Failed to derive bump for `entry`"),
+        )
+    );
+    let mut project_pools = ctx.accounts.project_pools.clone().into_inner();
+    if project_pools.project == Pubkey::default() {
+        project_pools = ProjectPools::new(
+            project_key,
+            *ctx.bumps
+                .get("project_pools")
+                .expect("Failed to derive bump for `project_pools`"),
+        );
+    }
+    project_pools.pools.push(pool_key);
+    set_and_maybe_realloc(
+        &mut ctx.accounts.project_pools,
+        &project_pools,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    ctx.accounts.project_pools.set_inner(project_pools);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    series_id: u64,
+)]
+pub struct CarryOverProject<'info> {
+    #[account(
+        seeds = [
+            RoundSeries::SEED_PREFIX.as_bytes(),
+            series_id.to_le_bytes().as_ref(),
+        ],
+        bump = series.bump,
+    )]
+    pub series: Box<Account<'info, RoundSeries>>,
+    #[account(
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            previous_pool.pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = previous_pool.bump,
+        constraint = series.previous_pool_id == Some(previous_pool.pool_id)
+            @ ProtocolError::SeriesPoolMismatch,
+    )]
+    pub previous_pool: Box<Account<'info, Pool>>,
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool.pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+        constraint = series.current_pool_id == Some(pool.pool_id)
+            @ ProtocolError::SeriesPoolMismatch,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    #[account(
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project.project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init,
+        space = PoolEntry::SPACE,
+        payer = payer,
+        seeds = [
+            PoolEntry::SEED_PREFIX.as_bytes(),
+            pool.key().as_ref(),
+            project.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub entry: Account<'info, PoolEntry>,
+    #[account(
+        init_if_needed,
+        space = ProjectPools::SPACE,
+        payer = payer,
+        seeds = [
+            ProjectPools::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub project_pools: Account<'info, ProjectPools>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,37 +1,58 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token, associated_token};
+use anchor_spl::{associated_token, token_interface::{Mint, TokenAccount, TokenInterface}};
 
 use crate::error::ProtocolError;
+use crate::events::PayoutClaimedEvent;
 use crate::state::{
+    config::*,
     pool::*,
     project::*,
+    registry::*,
 };
+use crate::util::{set_pool_and_maybe_realloc, transfer_fees, transfer_tokens};
 
-/// Claim's a project's payout from a pool
-/// that has completed.
+/// Claim's a project's payout in a mint from
+/// a pool that has been finalized.
 pub fn claim_payout(
     ctx: Context<ClaimPayout>,
     _project_id: u64,
     _pool_id: u64,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Withdrawals)?;
+
     let payer_key = &mut ctx.accounts.payer.key();
     let project = &mut ctx.accounts.project;
     let pool = &mut ctx.accounts.pool;
     let current_time = Clock::get()?.unix_timestamp as u64;
 
-    let mut pool_data = pool.clone().into_inner();
+    let pool_data = pool.clone().into_inner();
 
     project.is_active()?;
 
-    require!(current_time > pool.end, ProtocolError::PoolStillActive);
+    pool.can_claim()?;
     require!(project.admins.contains(&payer_key), ProtocolError::NotAuthorized);
 
-    if let Some(participant) = pool_data.project_shares.iter_mut().find(|p| p.project_key == project.key()) {
-        require!(participant.claimed == false, ProtocolError::AlreadyClaimed);
-
-        let payout = participant.share_data.share * pool.total_funding as f64;
+    if let Some(participant) = pool_data.project_shares.iter().find(|p| p.project_key == project.key()) {
+        // Marks the payout as claimed, after checking the claim deadline.
+        // Payouts are tracked per mint, which can grow the pool
+        let mut new_data = pool_data.clone();
+        let payout = new_data.claim(&participant.project_key, &ctx.accounts.mint.key(), current_time)?;
+        new_data.maybe_mark_distributed(current_time)?;
+        set_pool_and_maybe_realloc(
+            pool,
+            new_data,
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
 
-        let mut adj_payout = payout as u64;
+        // Fees come out of the matching payout
+        let fees = ctx.accounts.config.calculate_fees(
+            FeeKind::Payout,
+            payout,
+            &[project.key(), *payer_key],
+            ctx.accounts.frontend_token_account.is_some(),
+        )?;
+        let adj_payout = payout - fees.total();
 
         msg!("Payout for {:?} is {:?}", project.key(), payout);
 
@@ -41,27 +62,48 @@ pub fn claim_payout(
         let seeds = vec![Pool::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
         let signer_seeds = vec![seeds.as_slice()];
 
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
-                    from: ctx.accounts.pool_token_account.to_account_info(),
-                    to: ctx.accounts.project_token_account.to_account_info(),
-                    authority: pool.to_account_info(),
-                },
-                &signer_seeds
-            ),
-            adj_payout,
+        transfer_fees(
+            &fees,
+            ctx.accounts.pool_token_account.to_account_info(),
+            pool.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_token_account.as_ref().map(|a| a.to_account_info()),
+            ctx.accounts.frontend_token_account.as_ref().map(|a| a.to_account_info()),
+            ctx.accounts.token_program.to_account_info(),
+            &signer_seeds,
         )?;
 
-        if let Some(original_participant) = pool.project_shares.iter_mut().find(|p| p.project_key == participant.project_key) {
-            original_participant.claimed = true;
-        };
-
-        adj_payout *= 10_u64.pow(6) as u64;
+        // The project may receive less than the payout if the
+        // mint charges a transfer fee
+        let received = transfer_tokens(
+            ctx.accounts.pool_token_account.to_account_info(),
+            ctx.accounts.project_token_account.to_account_info(),
+            pool.to_account_info(),
+            &ctx.accounts.mint,
+            ctx.accounts.token_program.to_account_info(),
+            &signer_seeds,
+            adj_payout,
+        )?;
 
-        project.raised += adj_payout;
-        project.balance += adj_payout;
+        project.deposit(ctx.accounts.mint.key(), ctx.accounts.mint.decimals, received)?;
+        project.refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts);
+
+        // Entries are only recorded for projects that entered after
+        // registries were added
+        if let Some(entry) = &mut ctx.accounts.entry {
+            entry.share = participant.share_data.share;
+            entry.claimed += received;
+        }
+
+        emit!(PayoutClaimedEvent {
+            pool: pool.key(),
+            project: project.key(),
+            mint: ctx.accounts.mint.key(),
+            amount: received,
+            protocol_fee: fees.protocol,
+            frontend_fee: fees.frontend,
+            frontend: ctx.accounts.frontend_token_account.as_ref().map(|a| a.owner),
+        });
     } else {
         return Err(ProtocolError::NotInPool.into());
     };
@@ -76,6 +118,7 @@ pub fn claim_payout(
 )]
 pub struct ClaimPayout<'info> {
     #[account( 
+        mut,
         seeds = [
             Project::SEED_PREFIX.as_bytes(),
             project.project_id.to_le_bytes().as_ref(),
@@ -92,22 +135,50 @@ pub struct ClaimPayout<'info> {
         bump = pool.bump,
     )]
     pub pool: Account<'info, Pool>,
-    pub mint: Account<'info, token::Mint>,
+    #[account(
+        mut,
+        seeds = [
+            PoolEntry::SEED_PREFIX.as_bytes(),
+            pool.key().as_ref(),
+            project.key().as_ref(),
+        ],
+        bump = entry.bump,
+    )]
+    pub entry: Option<Account<'info, PoolEntry>>,
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = project,
     )]
-    pub project_token_account: Account<'info, token::TokenAccount>,
+    pub project_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = pool,
     )]
-    pub pool_token_account: Account<'info, token::TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = treasury_token_account.owner == config.treasury()?
+            @ ProtocolError::InvalidTreasury,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives the frontend fee, if passed in
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub frontend_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, token::Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
 }
\ No newline at end of file
@@ -1,25 +1,21 @@
 use anchor_lang::prelude::*;
 
 use crate::{state::{
+    config::*,
     milestone::*,
     project::*,
 }, error::ProtocolError};
 
-/// Closes a 'Milestone'
+/// Closes a `Milestone`, returning its rent to whoever paid for it
 pub fn close_milestone(
     ctx: Context<CloseMilestone>,
 ) -> Result<()> {
-    let payer_key = &mut ctx.accounts.payer.key();
-    let project = &mut ctx.accounts.project;
-    let milestone = &mut ctx.accounts.milestone;
+    ctx.accounts.config.check(Feature::Projects)?;
 
-    milestone.is_active()?;
+    let payer_key = ctx.accounts.payer.key();
+    let project = &ctx.accounts.project;
 
-    if project.admins.contains(&payer_key) {
-        milestone.status = MilestoneStatus::Closed;
-    } else {
-        return Err(ProtocolError::NotAuthorized.into());
-    }
+    require!(project.admins.contains(&payer_key), ProtocolError::NotAuthorized);
 
     Ok(())
 }
@@ -27,6 +23,8 @@ pub fn close_milestone(
 #[derive(Accounts)]
 pub struct CloseMilestone<'info> {
     #[account( 
+        mut,
+        close = rent_payer,
         seeds = [
             Milestone::SEED_PREFIX.as_bytes(),
             milestone.name.as_ref(),
@@ -36,9 +34,22 @@ pub struct CloseMilestone<'info> {
         bump = milestone.bump,
     )]
     pub milestone: Account<'info, Milestone>,
-    #[account(mut)]
     pub project: Account<'info, Project>,
+    /// CHECK: Receives the rent, checked against the milestone.
+    /// Legacy milestones didn't record a payer, so their rent goes to the admin.
+    #[account(
+        mut,
+        constraint = rent_payer.key() == milestone.rent_payer
+            || (milestone.rent_payer == Pubkey::default() && rent_payer.key() == payer.key())
+            @ ProtocolError::NotAuthorized,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+
+use crate::error::ProtocolError;
+use crate::state::pool::*;
+use crate::state::config::*;
+
+/// Closes a pool that's been distributed or cancelled, along with
+/// its token account. Rent paid into the pool is returned to its
+/// creator, whose account is passed in as a remaining account if
+/// they aren't the closing admin, along with the pool's token
+/// accounts for any other mints it was funded in.
+pub fn close_pool<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClosePool<'info>>,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let payer_key = ctx.accounts.payer.key();
+    let pool = &ctx.accounts.pool;
+
+    require!(pool.admins.contains(&payer_key), ProtocolError::NotAuthorized);
+
+    pool.can_close()?;
+
+    require!(
+        ctx.accounts.pool_token_account.amount == 0,
+        ProtocolError::AccountHoldsFunds
+    );
+
+    // Every mint the pool was funded in must be emptied too
+    for mint in pool.funding_mints() {
+        if mint == ctx.accounts.mint.key() {
+            continue;
+        }
+        let vault = ctx.remaining_accounts
+            .iter()
+            .filter_map(|a| InterfaceAccount::<TokenAccount>::try_from(a).ok())
+            .find(|v| v.mint == mint && v.owner == pool.key())
+            .ok_or(ProtocolError::PoolVaultMissing)?;
+        require!(vault.amount == 0, ProtocolError::AccountHoldsFunds);
+    }
+
+    let bump = pool.bump.to_le_bytes();
+    let id_ref = pool.pool_id.to_le_bytes();
+
+    let seeds = vec![Pool::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    token_interface::close_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::CloseAccount {
+                account: ctx.accounts.pool_token_account.to_account_info(),
+                destination: ctx.accounts.payer.to_account_info(),
+                authority: pool.to_account_info(),
+            },
+            &signer_seeds,
+        ),
+    )?;
+
+    // Return recorded rent, the remainder goes to the closing admin
+    let pool_info = pool.to_account_info();
+    let payer_info = ctx.accounts.payer.to_account_info();
+    for payment in &pool.rent_payers {
+        let rent_payer = ctx.remaining_accounts
+            .iter()
+            .chain(std::iter::once(&payer_info))
+            .find(|a| a.key() == payment.payer)
+            .ok_or(ProtocolError::RentPayerMissing)?;
+        let lamports = payment.lamports.min(pool_info.lamports());
+
+        **pool_info.try_borrow_mut_lamports()? -= lamports;
+        **rent_payer.try_borrow_mut_lamports()? += lamports;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct ClosePool<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = pool,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
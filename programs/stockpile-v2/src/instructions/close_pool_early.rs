@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::*, pool::*}, error::ProtocolError};
+
+/// Ends an active round before its scheduled end date.
+/// Only allowed once the round has run for the minimum
+/// pool duration.
+pub fn close_pool_early(
+    ctx: Context<ClosePoolEarly>,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let payer = &ctx.accounts.payer;
+
+    require!(pool.admins.contains(&payer.key()), ProtocolError::NotAuthorized);
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    pool.close_early(current_time)
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct ClosePoolEarly<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
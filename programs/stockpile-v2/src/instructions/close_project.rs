@@ -1,29 +1,63 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
 
-use crate::state::project::*;
+use crate::state::{pool::*, project::*, registry::*};
 use crate::error::ProtocolError;
+use crate::state::config::*;
 
-/// Closes a 'Project'
-pub fn close_project(
-    ctx: Context<CloseProject>,
+/// Closes a `Project`, returning its rent to whoever paid for it.
+/// A project can't be closed while it still holds funds, or while
+/// a pool it entered could still pay it out. Its token account for
+/// every mint it holds, and every pool in its `ProjectPools`, are
+/// passed in as remaining accounts.
+pub fn close_project<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseProject<'info>>,
 ) -> Result<()> {
-    let payer_key = &mut ctx.accounts.payer.key();
-    let project = &mut ctx.accounts.project;
+    ctx.accounts.config.check(Feature::Projects)?;
 
-    project.is_active()?;
+    let payer_key = ctx.accounts.payer.key();
+    let project = &ctx.accounts.project;
 
-    if project.admins.contains(&payer_key) {
-        project.status = ProjectStatus::Closed;
-    } else {
-        return Err(ProtocolError::NotAuthorized.into());
-    };
+    require!(project.admins.contains(&payer_key), ProtocolError::NotAuthorized);
+
+    // Every pool the project entered, other than ones already closed
+    let mut pool_states = vec![];
+    let project_pools = &ctx.accounts.project_pools;
+    if !project_pools.data_is_empty() {
+        let project_pools = ProjectPools::try_deserialize(&mut &project_pools.try_borrow_data()?[..])?;
+        for pool_key in project_pools.pools {
+            let pool_info = ctx.remaining_accounts
+                .iter()
+                .find(|a| a.key() == pool_key)
+                .ok_or(ProtocolError::ProjectPoolsUnsettled)?;
+            if pool_info.data_is_empty() {
+                continue;
+            }
+            pool_states.push(Account::<Pool>::try_from(pool_info)?.pool_state.clone());
+        }
+    }
+
+    project.can_close(&pool_states)?;
+
+    // Tokens sent straight to the project's token accounts
+    // aren't in its balances, so they must be empty too
+    for balance in &project.balances {
+        let vault = ctx.remaining_accounts
+            .iter()
+            .filter_map(|a| InterfaceAccount::<TokenAccount>::try_from(a).ok())
+            .find(|v| v.mint == balance.mint && v.owner == project.key())
+            .ok_or(ProtocolError::ProjectVaultMissing)?;
+        require!(vault.amount == 0, ProtocolError::AccountHoldsFunds);
+    }
 
     Ok(())
 }
 
 #[derive(Accounts)]
 pub struct CloseProject<'info> {
-    #[account( 
+    #[account(
+        mut,
+        close = rent_payer,
         seeds = [
             Project::SEED_PREFIX.as_bytes(),
             project.project_id.to_le_bytes().as_ref(),
@@ -31,7 +65,30 @@ pub struct CloseProject<'info> {
         bump = project.bump,
     )]
     pub project: Account<'info, Project>,
+    /// CHECK: The project's pools, which don't exist if it never entered one
+    #[account(
+        seeds = [
+            ProjectPools::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub project_pools: UncheckedAccount<'info>,
+    /// CHECK: Receives the rent, checked against the project.
+    /// Legacy projects didn't record a payer, so their rent goes to the admin.
+    #[account(
+        mut,
+        constraint = rent_payer.key() == project.rent_payer
+            || (project.rent_payer == Pubkey::default() && rent_payer.key() == payer.key())
+            @ ProtocolError::NotAuthorized,
+    )]
+    pub rent_payer: UncheckedAccount<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-}
\ No newline at end of file
+}
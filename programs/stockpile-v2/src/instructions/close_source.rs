@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+use crate::state::{
+    config::*,
+    pool::*,
+    source::*,
+};
+
+/// Closes a `FundingSource`, returning its rent to its authority.
+/// The source must have nothing left in its pool, either because
+/// it was refunded or paid out, or because the pool was closed.
+pub fn close_source(ctx: Context<CloseSource>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Funding)?;
+
+    let source = &ctx.accounts.source;
+    let pool_info = ctx.accounts.pool.to_account_info();
+
+    if !pool_info.data_is_empty() {
+        let pool = Account::<Pool>::try_from(&pool_info)?;
+        require!(
+            pool.is_source_settled(&source.key(), &source.refunded),
+            ProtocolError::SourceNotSettled
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseSource<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            FundingSource::SEED_PREFIX.as_bytes(),
+            source.name.as_ref(),
+            source.pool_id.to_le_bytes().as_ref(),
+            source.amount.to_le_bytes().as_ref(),
+            source.authority.as_ref(),
+        ],
+        bump = source.bump,
+        has_one = authority @ ProtocolError::NotAuthorized,
+    )]
+    pub source: Account<'info, FundingSource>,
+    /// CHECK: The source's pool, which may already be closed
+    #[account(
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            source.pool_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
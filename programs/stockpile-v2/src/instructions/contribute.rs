@@ -1,34 +1,93 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token, associated_token};
+use anchor_spl::{associated_token, token_interface::{Mint, TokenAccount, TokenInterface}};
 
-use crate::state::project::*;
-use crate::util::mint_is_supported;
+use crate::error::ProtocolError;
+use crate::events::ContributionEvent;
+use crate::state::{
+    config::*,
+    project::*,
+    receipt::*,
+};
+use crate::util::{transfer_fees, transfer_tokens};
 
+/// Contributes directly to a project. Each contribution is
+/// recorded on the donor's `ContributionReceipt` for the mint.
 pub fn contribute(
     ctx: Context<Contribute>,
     _project_id: u64,
     amount: u64,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Contributions)?;
+
     // Check to make sure the token is supported
-    mint_is_supported(&ctx.accounts.mint.key())?;
+    ctx.accounts.config.check_mint(&ctx.accounts.mint.to_account_info())?;
+
+    // Check to make sure the project is accepting contributions
+    ctx.accounts.project.can_contribute()?;
+
+    // Fees come out of the contribution
+    let fees = ctx.accounts.config.calculate_fees(
+        FeeKind::Contribution,
+        amount,
+        &[ctx.accounts.payer.key(), ctx.accounts.project.key()],
+        ctx.accounts.frontend_token_account.is_some(),
+    )?;
+    let amount = amount - fees.total();
+
+    transfer_fees(
+        &fees,
+        ctx.accounts.payer_token_account.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.treasury_token_account.as_ref().map(|a| a.to_account_info()),
+        ctx.accounts.frontend_token_account.as_ref().map(|a| a.to_account_info()),
+        ctx.accounts.token_program.to_account_info(),
+        &[],
+    )?;
 
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.payer_token_account.to_account_info(),
-                to: ctx.accounts.project_token_account.to_account_info(),
-                authority: ctx.accounts.payer.to_account_info(),
-            },
-        ),
+    // Only what arrives after any transfer fee is recorded
+    let amount = transfer_tokens(
+        ctx.accounts.payer_token_account.to_account_info(),
+        ctx.accounts.project_token_account.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &[],
         amount,
     )?;
 
     //Increment fields
-    ctx.accounts.project.raised += amount;
-    ctx.accounts.project.balance += amount;
+    ctx.accounts.project.deposit(ctx.accounts.mint.key(), ctx.accounts.mint.decimals, amount)?;
     ctx.accounts.project.contributors += 1;
 
+    // Oracle accounts are passed in as remaining accounts
+    ctx.accounts.project.refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts);
+
+    // Record the contribution
+    let receipt = &mut ctx.accounts.receipt;
+    if receipt.donor == Pubkey::default() {
+        receipt.version = ContributionReceipt::VERSION;
+        receipt.project = ctx.accounts.project.key();
+        receipt.donor = ctx.accounts.payer.key();
+        receipt.mint = ctx.accounts.mint.key();
+        receipt.bump = *ctx.bumps
+            .get("receipt")
+            .expect("Failed to derive bump for `receipt`");
+    }
+    receipt.amount += amount;
+    receipt.fees += fees.total();
+
+    emit!(ContributionEvent {
+        project: ctx.accounts.project.key(),
+        pool: None,
+        donor: ctx.accounts.payer.key(),
+        mint: ctx.accounts.mint.key(),
+        amount,
+        protocol_fee: fees.protocol,
+        frontend_fee: fees.frontend,
+        frontend: ctx.accounts.frontend_token_account.as_ref().map(|a| a.owner),
+    });
+
     Ok(())
 }
 
@@ -47,22 +106,53 @@ pub struct Contribute<'info> {
         bump = project.bump,
     )]
     pub project: Account<'info, Project>,
-    pub mint: Account<'info, token::Mint>,
+    #[account(
+        init_if_needed,
+        space = ContributionReceipt::SPACE,
+        payer = payer,
+        seeds = [
+            ContributionReceipt::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+            payer.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub receipt: Account<'info, ContributionReceipt>,
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = project,
     )]
-    pub project_token_account: Account<'info, token::TokenAccount>,
+    pub project_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = payer,
     )]
-    pub payer_token_account: Account<'info, token::TokenAccount>,
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = treasury_token_account.owner == config.treasury()?
+            @ ProtocolError::InvalidTreasury,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives the frontend fee, if passed in
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub frontend_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, token::Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token, token_interface::{Mint, TokenAccount, TokenInterface}};
+
+use solana_gateway::Gateway;
+
+use crate::error::ProtocolError;
+use crate::events::{ContributionEvent, SoftCloseExtendedEvent};
+use crate::state::{
+    config::*,
+    pool::*,
+    project::*,
+};
+use crate::util::{
+    MAX_BATCH_LEN,
+    USDC_USD_PRICE_FEED_ID,
+    set_pool_and_maybe_realloc,
+    to_pubkey,
+    transfer_fees,
+    transfer_tokens,
+};
+
+/// A single contribution in a batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchContribution {
+    pub project_id: u64,
+    pub amount: u64,
+}
+
+/// Contributes to several projects in a pool at once. Each project
+/// and its token account are passed in as a pair of writable remaining
+/// accounts, in the same order as `contributions`, followed by any
+/// oracle accounts. The Civic pass is verified, and the shares are
+/// recalculated, once for the whole batch.
+pub fn contribute_batch<'info>(
+    ctx: Context<'_, '_, '_, 'info, ContributeBatch<'info>>,
+    _pool_id: u64,
+    contributions: Vec<BatchContribution>,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Contributions)?;
+
+    let payer = ctx.accounts.payer.key();
+    let mint = &ctx.accounts.mint;
+
+    require!(!contributions.is_empty(), ProtocolError::InvalidBatch);
+    require!(contributions.len() <= MAX_BATCH_LEN, ProtocolError::InvalidBatch);
+    require!(
+        ctx.remaining_accounts.len() >= contributions.len() * 2,
+        ProtocolError::InvalidBatch
+    );
+    for (i, contribution) in contributions.iter().enumerate() {
+        require!(
+            contributions[..i].iter().all(|c| c.project_id != contribution.project_id),
+            ProtocolError::InvalidBatch
+        );
+    }
+
+    // Check to make sure the token is supported, and
+    // that the pool can price it as a vote
+    ctx.accounts.config.check_mint(&mint.to_account_info())?;
+    ctx.accounts.pool.oracle.check_mint(&mint.key(), mint.decimals)?;
+
+    // Check to make sure the pool is not closed
+    ctx.accounts.pool.is_active()?;
+
+    // Perform Civic pass verification
+    Gateway::verify_gateway_token_account_info(
+        &ctx.accounts.gateway_token_account.to_account_info(),
+        &payer,
+        &ctx.accounts.gatekeeper_network.key(),
+        None,
+    ).map_err(|_e| {
+        msg!("Gateway token verification failed.");
+        ProtocolError::CivicFailure
+    })?;
+
+    let (project_accounts, oracle_accounts) = ctx.remaining_accounts.split_at(contributions.len() * 2);
+    let mut oracle_accounts = oracle_accounts.to_vec();
+    if let Some(pyth_usdc_usd) = &ctx.accounts.pyth_usdc_usd {
+        oracle_accounts.push(pyth_usdc_usd.to_account_info());
+    }
+    let oracle = ctx.accounts.config.oracle();
+
+    // Snapshot the round to measure the batch against the soft close
+    let activity_before = ctx.accounts.pool.activity();
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    let mut pool_data = ctx.accounts.pool.clone().into_inner();
+    let mut events = Vec::with_capacity(contributions.len());
+
+    for (contribution, accounts) in contributions.iter().zip(project_accounts.chunks(2)) {
+        let mut project = Account::<Project>::try_from(&accounts[0])?;
+        let project_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        require!(project.project_id == contribution.project_id, ProtocolError::InvalidBatch);
+        require!(project_token_account.mint == mint.key(), ProtocolError::InvalidBatch);
+        require!(project_token_account.owner == project.key(), ProtocolError::InvalidBatch);
+
+        // Check to make sure the project can take votes
+        project.can_contribute()?;
+        project.can_join_pool()?;
+
+        // Fees come out of each vote, so only what
+        // reaches the project is matched
+        let fees = ctx.accounts.config.calculate_fees(
+            FeeKind::Contribution,
+            contribution.amount,
+            &[payer, project.key()],
+            ctx.accounts.frontend_token_account.is_some(),
+        )?;
+        let amount = contribution.amount - fees.total();
+
+        transfer_fees(
+            &fees,
+            ctx.accounts.payer_token_account.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            mint,
+            ctx.accounts.treasury_token_account.as_ref().map(|a| a.to_account_info()),
+            ctx.accounts.frontend_token_account.as_ref().map(|a| a.to_account_info()),
+            ctx.accounts.token_program.to_account_info(),
+            &[],
+        )?;
+
+        // Transfer the vote to the project. Only what arrives
+        // after any transfer fee is counted.
+        let amount = transfer_tokens(
+            ctx.accounts.payer_token_account.to_account_info(),
+            project_token_account.to_account_info(),
+            ctx.accounts.payer.to_account_info(),
+            mint,
+            ctx.accounts.token_program.to_account_info(),
+            &[],
+            amount,
+        )?;
+        pool_data.record_vote(&project.key(), payer, mint.key(), amount)?;
+
+        // Increment fields
+        project.deposit(mint.key(), mint.decimals, amount)?;
+        project.contributors += 1;
+        project.refresh_value(&oracle, &oracle_accounts);
+        project.exit(&crate::ID)?;
+
+        events.push(ContributionEvent {
+            project: project.key(),
+            pool: Some(ctx.accounts.pool.key()),
+            donor: payer,
+            mint: mint.key(),
+            amount,
+            protocol_fee: fees.protocol,
+            frontend_fee: fees.frontend,
+            frontend: ctx.accounts.frontend_token_account.as_ref().map(|a| a.owner),
+        });
+    }
+
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.pool,
+        pool_data,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    // Update the QF algorithm once for the whole batch
+    ctx.accounts.pool.update_shares(&oracle_accounts)?;
+
+    if let Some(new_end) = ctx.accounts.pool.apply_soft_close(&activity_before, current_time) {
+        emit!(SoftCloseExtendedEvent {
+            pool: ctx.accounts.pool.key(),
+            new_end,
+            extended_by: ctx.accounts.pool.extended_by,
+        });
+    }
+
+    for event in events {
+        emit!(event);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct ContributeBatch<'info> {
+    /// CHECK: Pyth will check this. Optional for fixed peg pools.
+    #[account(
+        address = to_pubkey(USDC_USD_PRICE_FEED_ID)
+            @ ProtocolError::PythAccountInvalid
+    )]
+    pub pyth_usdc_usd: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = treasury_token_account.owner == config.treasury()?
+            @ ProtocolError::InvalidTreasury,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives the frontend fee, if passed in
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub frontend_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: This is not unsafe because this account isn't written to
+    pub gateway_token_account: AccountInfo<'info>,
+    /// CHECK: Pinned to the protocol's gatekeeper network, and isn't written to
+    #[account(
+        address = config.gatekeeper_network @ ProtocolError::CivicFailure
+    )]
+    pub gatekeeper_network: AccountInfo<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+}
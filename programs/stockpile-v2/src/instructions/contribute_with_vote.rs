@@ -1,18 +1,21 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token, associated_token};
+use anchor_spl::{associated_token, token_interface::{Mint, TokenAccount, TokenInterface}};
 
 use solana_gateway::Gateway;
 
 use crate::error::ProtocolError;
+use crate::events::{ContributionEvent, SoftCloseExtendedEvent};
 use crate::state::{
+    config::*,
     pool::*,
     project::*,
 };
 use crate::util::{ 
     USDC_USD_PRICE_FEED_ID, 
-    set_and_maybe_realloc,
-    mint_is_supported,
-    to_pubkey
+    set_pool_and_maybe_realloc,
+    to_pubkey,
+    transfer_fees,
+    transfer_tokens,
 };
 
 // Makes a contribution to a fundraiser that is
@@ -20,22 +23,30 @@ use crate::util::{
 // that the fundraiser has invoked the "join_pool"
 // instruction, and is actively participating. Also
 // requires the payer have a valid Civic pass.
-pub fn contribute_with_vote(
-    ctx: Context<ContributeWithVote>,
+pub fn contribute_with_vote<'info>(
+    ctx: Context<'_, '_, '_, 'info, ContributeWithVote<'info>>,
     _pool_id: u64,
     _project_id: u64,
     amount: u64,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Contributions)?;
+
     // Define verification options
     let gatekeeper_network = ctx.accounts.gatekeeper_network.key();
     let payer = ctx.accounts.payer.key();
 
-    // Check to make sure the token is supported
-    mint_is_supported(&ctx.accounts.mint.key())?;
+    // Check to make sure the token is supported, and
+    // that the pool can price it as a vote
+    ctx.accounts.config.check_mint(&ctx.accounts.mint.to_account_info())?;
+    ctx.accounts.pool.oracle.check_mint(&ctx.accounts.mint.key(), ctx.accounts.mint.decimals)?;
 
     // Check to make sure the pool is not closed
     ctx.accounts.pool.is_active()?;
 
+    // Check to make sure the project can take votes
+    ctx.accounts.project.can_contribute()?;
+    ctx.accounts.project.can_join_pool()?;
+
     // Perform Civic pass verification
     Gateway::verify_gateway_token_account_info(
         &ctx.accounts.gateway_token_account.to_account_info(), 
@@ -45,61 +56,87 @@ pub fn contribute_with_vote(
     ).map_err(|_e| {
         msg!("Gateway token verification failed.");
         ProtocolError::CivicFailure
-    });
+    })?;
 
-    // Add the project to the shares, if it doesn't exist
-    let project_key = ctx.accounts.project.key();
-    let mut pool_data = ctx.accounts.pool.clone().into_inner();
+    // Fees come out of the vote, so only what
+    // reaches the project is matched
+    let fees = ctx.accounts.config.calculate_fees(
+        FeeKind::Contribution,
+        amount,
+        &[payer, ctx.accounts.project.key()],
+        ctx.accounts.frontend_token_account.is_some(),
+    )?;
+    let amount = amount - fees.total();
 
-    // Iterate through the Participants, and 
-    // check if the project exists in the pool
-    // If not: break function and return error
-    if let Some(participant) = pool_data.project_shares.iter_mut().find(|p| p.project_key == project_key) {
-        let vote_ticket = VoteTicket::new(
-            payer, 
-            Some(ctx.accounts.mint.key()), 
-            amount, 
-        );
-
-    if let Some(payer_vote_ticket) = participant.share_data.votes.iter_mut().find(|t| t.payer == payer) {
-        payer_vote_ticket.amount += amount;
-    } else {
-        participant.share_data.votes.push(vote_ticket);
-    }
-        set_and_maybe_realloc(
-            &mut ctx.accounts.pool, 
-            &pool_data, 
-            ctx.accounts.payer.to_account_info(), 
-            ctx.accounts.system_program.to_account_info()
-        )?;
-
-        ctx.accounts.pool.set_inner(pool_data);
-    } else {
-        return Err(ProtocolError::NotInPool.into());
-    }
+    // Snapshot the round to measure this vote against the soft close
+    let activity_before = ctx.accounts.pool.activity();
+    let current_time = Clock::get()?.unix_timestamp as u64;
 
-    // Transfer the vote to the project
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.payer_token_account.to_account_info(),
-                to: ctx.accounts.project_token_account.to_account_info(),
-                authority: ctx.accounts.payer.to_account_info(),
-            },
-        ),
+    transfer_fees(
+        &fees,
+        ctx.accounts.payer_token_account.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.treasury_token_account.as_ref().map(|a| a.to_account_info()),
+        ctx.accounts.frontend_token_account.as_ref().map(|a| a.to_account_info()),
+        ctx.accounts.token_program.to_account_info(),
+        &[],
+    )?;
+
+    // Transfer the vote to the project. Only what arrives
+    // after any transfer fee is counted.
+    let amount = transfer_tokens(
+        ctx.accounts.payer_token_account.to_account_info(),
+        ctx.accounts.project_token_account.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &[],
         amount,
     )?;
 
+    // Record the vote against the project, which must be in the pool
+    let project_key = ctx.accounts.project.key();
+    let mut pool_data = ctx.accounts.pool.clone().into_inner();
+    pool_data.record_vote(&project_key, payer, ctx.accounts.mint.key(), amount)?;
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.pool, 
+        pool_data, 
+        ctx.accounts.payer.to_account_info(), 
+        ctx.accounts.system_program.to_account_info()
+    )?;
+
     // Increment fields
-    ctx.accounts.project.raised += amount;
-    ctx.accounts.project.balance += amount;
+    ctx.accounts.project.deposit(ctx.accounts.mint.key(), ctx.accounts.mint.decimals, amount)?;
     ctx.accounts.project.contributors += 1;
 
-    // Update the QF algorithm
-    ctx.accounts.pool.update_shares(
-        ctx.accounts.pyth_usdc_usd.to_account_info(),
-    )?;
+    // Update the QF algorithm. Additional oracle
+    // accounts are passed in as remaining accounts.
+    let mut oracle_accounts = ctx.remaining_accounts.to_vec();
+    if let Some(pyth_usdc_usd) = &ctx.accounts.pyth_usdc_usd {
+        oracle_accounts.push(pyth_usdc_usd.to_account_info());
+    }
+    ctx.accounts.pool.update_shares(&oracle_accounts)?;
+
+    if let Some(new_end) = ctx.accounts.pool.apply_soft_close(&activity_before, current_time) {
+        emit!(SoftCloseExtendedEvent {
+            pool: ctx.accounts.pool.key(),
+            new_end,
+            extended_by: ctx.accounts.pool.extended_by,
+        });
+    }
+    ctx.accounts.project.refresh_value(&ctx.accounts.config.oracle(), &oracle_accounts);
+
+    emit!(ContributionEvent {
+        project: project_key,
+        pool: Some(ctx.accounts.pool.key()),
+        donor: payer,
+        mint: ctx.accounts.mint.key(),
+        amount,
+        protocol_fee: fees.protocol,
+        frontend_fee: fees.frontend,
+        frontend: ctx.accounts.frontend_token_account.as_ref().map(|a| a.owner),
+    });
 
     Ok(())
 }
@@ -111,12 +148,12 @@ pub fn contribute_with_vote(
     _amount: u64, // Anchor barfs if you don't have all ix args
 )]
 pub struct ContributeWithVote<'info> {
-    /// CHECK: Pyth will check this
+    /// CHECK: Pyth will check this. Optional for fixed peg pools.
     #[account(
         address = to_pubkey(USDC_USD_PRICE_FEED_ID)
             @ ProtocolError::PythAccountInvalid
     )]
-    pub pyth_usdc_usd: UncheckedAccount<'info>,
+    pub pyth_usdc_usd: Option<UncheckedAccount<'info>>,
     #[account( 
         mut,
         seeds = [
@@ -127,6 +164,7 @@ pub struct ContributeWithVote<'info> {
     )]
     pub pool: Box<Account<'info, Pool>>,
     #[account( 
+        mut,
         seeds = [
             Project::SEED_PREFIX.as_bytes(),
             project_id.to_le_bytes().as_ref(),
@@ -134,26 +172,47 @@ pub struct ContributeWithVote<'info> {
         bump = project.bump,
     )]
     pub project: Box<Account<'info, Project>>,
-    pub mint: Account<'info, token::Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = project,
     )]
-    pub project_token_account: Account<'info, token::TokenAccount>,
+    pub project_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = payer,
     )]
-    pub payer_token_account: Account<'info, token::TokenAccount>,
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = treasury_token_account.owner == config.treasury()?
+            @ ProtocolError::InvalidTreasury,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    /// Receives the frontend fee, if passed in
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub frontend_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
     /// CHECK: This is not unsafe because this account isn't written to
     pub gateway_token_account: AccountInfo<'info>,
-    /// CHECK: This is not unsafe because this account isn't written to
+    /// CHECK: Pinned to the protocol's gatekeeper network, and isn't written to
+    #[account(
+        address = config.gatekeeper_network @ ProtocolError::CivicFailure
+    )]
     pub gatekeeper_network: AccountInfo<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, token::Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::{state::{
+    config::*,
     milestone::*,
     project::*,
 }, error::ProtocolError};
@@ -13,6 +14,9 @@ pub fn create_milestone(
     name: String,
     percentage: f64,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Projects)?;
+    ctx.accounts.config.validate_name(&name)?;
+
     let payer_key = &mut ctx.accounts.payer.key();
     let project = &mut ctx.accounts.project;
 
@@ -23,6 +27,7 @@ pub fn create_milestone(
                 name,
                 percentage,
                 project.key(),
+                *payer_key,
                 *ctx.bumps
                     .get("milestone")
                     .expect("Failed to derive bump for `project`"),
@@ -58,5 +63,10 @@ pub struct CreateMilestone<'info> {
     pub project: Account<'info, Project>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
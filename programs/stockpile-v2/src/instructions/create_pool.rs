@@ -1,16 +1,27 @@
 use anchor_lang::prelude::*;
 
+use crate::events::PoolCreatedEvent;
 use crate::state::pool::*;
+use crate::state::config::*;
+use crate::state::creator::*;
+use crate::state::registry::*;
+use crate::util::set_and_maybe_realloc;
 
+/// Creates a `Pool`, with an id allocated
+/// from the protocol's counter.
 pub fn create_pool(
     ctx: Context<CreatePool>,
-    pool_id: u64,
     name: String,
     start: u64,
     end: u64,
     admins: Vec<Pubkey>,
     access: PoolAccess,
+    caps: MatchingCaps,
 ) -> Result<()> {  
+    ctx.accounts.config.check(Feature::Pools)?;
+    ctx.accounts.config.validate_name(&name)?;
+    ctx.accounts.config.validate_admins(&admins)?;
+    let pool_id = ctx.accounts.config.take_pool_id()?;
 
     ctx.accounts.pool.set_inner(
         Pool::new(
@@ -20,33 +31,108 @@ pub fn create_pool(
             end,
             admins,
             access,
+            caps,
             *ctx.bumps
                 .get("pool")
                 .expect("Failed to derive bump for `pool`"),
         )?
     );
+
+    // New pools price mints with the protocol's feeds
+    let config = &ctx.accounts.config;
+    ctx.accounts.pool.oracle.feeds = config.mints.clone();
+    ctx.accounts.pool.oracle.max_staleness = config.limits.max_staleness;
+
+    // The creator's rent is returned to them when the pool is closed
+    let rent = ctx.accounts.pool.to_account_info().lamports();
+    ctx.accounts.pool.record_rent(ctx.accounts.payer.key(), rent);
+
+    // Index the pool under its creator
+    let mut index = ctx.accounts.creator_index.clone().into_inner();
+    if index.creator == Pubkey::default() {
+        index = CreatorIndex::new(
+            ctx.accounts.payer.key(),
+            *ctx.bumps
+                .get("creator_index")
+                .expect("Failed to derive bump for `creator_index`"),
+        );
+    }
+    index.pools.push(pool_id);
+    set_and_maybe_realloc(
+        &mut ctx.accounts.creator_index,
+        &index,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    ctx.accounts.creator_index.set_inner(index);
+
+    // List the pool in the directory, moving on
+    // to a new page once this one is full
+    let directory = &mut ctx.accounts.directory;
+    if directory.version == 0 {
+        directory.set_inner(
+            PoolDirectory::new(
+                ctx.accounts.config.directory_page,
+                *ctx.bumps
+                    .get("directory")
+                    .expect("Failed to derive bump for `directory`"),
+            )
+        );
+    }
+    if directory.add(ctx.accounts.pool.key())? {
+        ctx.accounts.config.directory_page += 1;
+    }
+
+    emit!(PoolCreatedEvent {
+        pool: ctx.accounts.pool.key(),
+        pool_id,
+        creator: ctx.accounts.payer.key(),
+    });
+
     Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(
-    pool_id: u64,
-    _name: String,
-    _start: u64,
-    _end: u64,
-)]
 pub struct CreatePool<'info> {
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     #[account(
         init, 
         space = Pool::SPACE,
         payer = payer, 
         seeds = [
             Pool::SEED_PREFIX.as_bytes(),
-            pool_id.to_le_bytes().as_ref(),
+            config.next_pool_id.to_le_bytes().as_ref(),
         ], 
         bump, 
     )]
     pub pool: Account<'info, Pool>,
+    #[account(
+        init_if_needed,
+        space = CreatorIndex::SPACE,
+        payer = payer,
+        seeds = [
+            CreatorIndex::SEED_PREFIX.as_bytes(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub creator_index: Account<'info, CreatorIndex>,
+    #[account(
+        init_if_needed,
+        space = PoolDirectory::SPACE,
+        payer = payer,
+        seeds = [
+            PoolDirectory::SEED_PREFIX.as_bytes(),
+            config.directory_page.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub directory: Account<'info, PoolDirectory>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
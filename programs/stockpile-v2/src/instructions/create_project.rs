@@ -1,18 +1,27 @@
 use anchor_lang::prelude::*;
 
+use crate::events::ProjectCreatedEvent;
 use crate::state::project::*;
+use crate::state::config::*;
+use crate::state::creator::*;
+use crate::util::set_and_maybe_realloc;
 
 /// Creates a `Project` for a project.
 /// This project can be re-entered in funding rounds and
 /// only needs to be created as a `Project` once.
+/// Its id is allocated from the protocol's counter.
 pub fn create_project(
     ctx: Context<CreateProject>,
-    project_id: u64,
     name: String,
     mut admins: Vec<Pubkey>,
     beneficiary: Pubkey,
     goal: u64,
+    campaign: Option<Campaign>,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Projects)?;
+    ctx.accounts.config.validate_name(&name)?;
+    ctx.accounts.config.validate_admins(&admins)?;
+    let project_id = ctx.accounts.config.take_project_id()?;
 
     if admins.len() == 0 {
         //Add beneficiary to admin vec
@@ -25,6 +34,8 @@ pub fn create_project(
                 admins,
                 goal,
                 beneficiary,
+                campaign,
+                ctx.accounts.payer.key(),
                 *ctx.bumps
                     .get("project")
                     .expect("Failed to derive bump for `project`"),
@@ -38,32 +49,73 @@ pub fn create_project(
                 admins,
                 goal,
                 beneficiary,
+                campaign,
+                ctx.accounts.payer.key(),
                 *ctx.bumps
                     .get("project")
                     .expect("Failed to derive bump for `project`"),
             )?
         );
     }
+
+    // Index the project under its creator
+    let mut index = ctx.accounts.creator_index.clone().into_inner();
+    if index.creator == Pubkey::default() {
+        index = CreatorIndex::new(
+            ctx.accounts.payer.key(),
+            *ctx.bumps
+                .get("creator_index")
+                .expect("Failed to derive bump for `creator_index`"),
+        );
+    }
+    index.projects.push(project_id);
+    set_and_maybe_realloc(
+        &mut ctx.accounts.creator_index,
+        &index,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    ctx.accounts.creator_index.set_inner(index);
+
+    emit!(ProjectCreatedEvent {
+        project: ctx.accounts.project.key(),
+        project_id,
+        creator: ctx.accounts.payer.key(),
+    });
+
     Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(
-    project_id: u64,
-    _name: String, // Anchor barfs if you don't have all ix args
-)]
 pub struct CreateProject<'info> {
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     #[account( 
         init,
         space = Project::SPACE,
         payer = payer,
         seeds = [
             Project::SEED_PREFIX.as_bytes(),
-            project_id.to_le_bytes().as_ref(),
+            config.next_project_id.to_le_bytes().as_ref(),
         ],
         bump,
     )]
     pub project: Account<'info, Project>,
+    #[account(
+        init_if_needed,
+        space = CreatorIndex::SPACE,
+        payer = payer,
+        seeds = [
+            CreatorIndex::SEED_PREFIX.as_bytes(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub creator_index: Account<'info, CreatorIndex>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub rent: Sysvar<'info, Rent>,
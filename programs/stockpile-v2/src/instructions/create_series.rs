@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+use crate::state::{
+    config::*,
+    series::*,
+};
+
+/// Creates a `RoundSeries`, with an id allocated from the
+/// protocol's counter. Rounds are spawned from its template
+/// by `spawn_round`, starting at `first_start`.
+pub fn create_series(
+    ctx: Context<CreateSeries>,
+    name: String,
+    admins: Vec<Pubkey>,
+    template: SeriesTemplate,
+    first_start: u64,
+    max_rounds: Option<u32>,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+    ctx.accounts.config.validate_name(&name)?;
+    ctx.accounts.config.validate_admins(&admins)?;
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    require!(first_start > current_time, ProtocolError::PoolInvalidStart);
+
+    let series_id = ctx.accounts.config.take_series_id()?;
+
+    ctx.accounts.series.set_inner(
+        RoundSeries::new(
+            series_id,
+            name,
+            admins,
+            template,
+            first_start,
+            max_rounds,
+            *ctx.bumps
+                .get("series")
+                .expect("Failed to derive bump for `series`"),
+        )?
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateSeries<'info> {
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    #[account(
+        init,
+        space = RoundSeries::SPACE,
+        payer = payer,
+        seeds = [
+            RoundSeries::SEED_PREFIX.as_bytes(),
+            config.next_series_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub series: Account<'info, RoundSeries>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -1,19 +1,25 @@
 use anchor_lang::prelude::*;
 
 use crate::state::source::*;
+use crate::state::config::*;
 
 pub fn create_source(
     ctx: Context<CreateSource>,
     name: String,
     pool_id: u64,
     amount: u64,
+    earmark: Earmark,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Funding)?;
+    ctx.accounts.config.validate_name(&name)?;
+
     ctx.accounts.source.set_inner(
         FundingSource::new(
             name,
             ctx.accounts.payer.key(),
             pool_id,
             amount,
+            earmark,
             *ctx.bumps
                 .get("source")
                 .expect("Failed to derive bump for `source`"),
@@ -29,7 +35,7 @@ pub fn create_source(
     amount: u64,
 )]
 pub struct CreateSource<'info> {
-    #[account( 
+    #[account(
         init,
         space = FundingSource::SPACE,
         payer = payer,
@@ -45,5 +51,10 @@ pub struct CreateSource<'info> {
     pub source: Account<'info, FundingSource>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
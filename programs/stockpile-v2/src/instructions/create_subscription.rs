@@ -0,0 +1,123 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+
+use crate::state::{
+    config::*,
+    project::*,
+    receipt::*,
+    subscription::*,
+};
+
+/// Creates a recurring `Subscription` to a project. The subscription
+/// is approved as a delegate over the donor's token account for
+/// `allowance`, which caps the total it can ever transfer.
+/// Note that a token account can only have one delegate at a time.
+/// Payments are recorded on the donor's `ContributionReceipt`.
+pub fn create_subscription(
+    ctx: Context<CreateSubscription>,
+    _project_id: u64,
+    amount: u64,
+    period: u64,
+    allowance: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Subscriptions)?;
+
+    // Check to make sure the token is supported
+    ctx.accounts.config.check_mint(&ctx.accounts.mint.to_account_info())?;
+
+    ctx.accounts.project.can_contribute()?;
+
+    ctx.accounts.subscription.set_inner(
+        Subscription::new(
+            ctx.accounts.payer.key(),
+            ctx.accounts.project.key(),
+            ctx.accounts.mint.key(),
+            amount,
+            period,
+            *ctx.bumps
+                .get("subscription")
+                .expect("Failed to derive bump for `subscription`"),
+        )?
+    );
+
+    let receipt = &mut ctx.accounts.receipt;
+    if receipt.donor == Pubkey::default() {
+        receipt.version = ContributionReceipt::VERSION;
+        receipt.project = ctx.accounts.project.key();
+        receipt.donor = ctx.accounts.payer.key();
+        receipt.mint = ctx.accounts.mint.key();
+        receipt.bump = *ctx.bumps
+            .get("receipt")
+            .expect("Failed to derive bump for `receipt`");
+    }
+
+    token_interface::approve(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            token_interface::Approve {
+                to: ctx.accounts.payer_token_account.to_account_info(),
+                delegate: ctx.accounts.subscription.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        allowance,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    project_id: u64,
+)]
+pub struct CreateSubscription<'info> {
+    #[account(
+        init,
+        space = Subscription::SPACE,
+        payer = payer,
+        seeds = [
+            Subscription::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        init_if_needed,
+        space = ContributionReceipt::SPACE,
+        payer = payer,
+        seeds = [
+            ContributionReceipt::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+            payer.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub receipt: Account<'info, ContributionReceipt>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
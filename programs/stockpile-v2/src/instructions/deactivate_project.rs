@@ -2,8 +2,11 @@ use anchor_lang::prelude::*;
 
 use crate::error::ProtocolError;
 use crate::state::project::*;
+use crate::state::config::*;
 
 pub fn deactivate_project(ctx: Context<DeactivateProject>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Projects)?;
+
     let project = &mut ctx.accounts.project;
     let agent = ctx.accounts.payer.key();
 
@@ -28,5 +31,10 @@ pub struct DeactivateProject<'info> {
     pub project: Account<'info, Project>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::ProtocolError;
+use crate::events::PayoutClaimedEvent;
+use crate::state::{
+    config::*,
+    pool::*,
+    project::*,
+    registry::*,
+};
+use crate::util::{set_pool_and_maybe_realloc, transfer_fees, transfer_tokens};
+
+/// Pays out matching in a mint to a page of a finalized pool's participants,
+/// so a round can be settled without every project claiming. Anyone
+/// can call this. Each participant is passed in as three writable
+/// remaining accounts: the project, its token account, and its
+/// `PoolEntry` address, which may not exist for projects that
+/// entered before registries were added. Participants with nothing
+/// left to claim in the mint, whose claim has expired, or whose
+/// project is deactivated, are skipped.
+pub fn distribute_payouts<'info>(
+    ctx: Context<'_, '_, '_, 'info, DistributePayouts<'info>>,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Withdrawals)?;
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let mint = &ctx.accounts.mint;
+    let pool_key = ctx.accounts.pool.key();
+
+    ctx.accounts.pool.can_claim()?;
+    require!(ctx.remaining_accounts.len() % 3 == 0, ProtocolError::InvalidBatch);
+
+    let bump = ctx.accounts.pool.bump.to_le_bytes();
+    let id_ref = ctx.accounts.pool.pool_id.to_le_bytes();
+    let seeds = vec![Pool::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    // Payouts are tracked per mint, which can grow the pool,
+    // so it's written back once the page has been paid out
+    let mut pool_data = ctx.accounts.pool.clone().into_inner();
+
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        let mut project = Account::<Project>::try_from(&accounts[0])?;
+        let project_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        require!(project_token_account.mint == mint.key(), ProtocolError::InvalidBatch);
+        require!(project_token_account.owner == project.key(), ProtocolError::InvalidBatch);
+
+        let participant = pool_data.project_shares
+            .iter()
+            .find(|p| p.project_key == project.key())
+            .ok_or(ProtocolError::NotInPool)?;
+        let settled = participant.forfeited
+            || (participant.claimed && pool_data.claimable(participant, &mint.key()) == 0);
+        let expired = !participant.claimed
+            && pool_data.claim_deadline().map_or(false, |d| current_time > d);
+        if settled || expired || project.is_active().is_err() {
+            msg!("Skipping {:?}", project.key());
+            continue;
+        }
+
+        let payout = pool_data.claim(&project.key(), &mint.key(), current_time)?;
+
+        // Fees come out of the matching payout
+        let fees = ctx.accounts.config.calculate_fees(
+            FeeKind::Payout,
+            payout,
+            &[project.key()],
+            false,
+        )?;
+        let adj_payout = payout - fees.total();
+
+        transfer_fees(
+            &fees,
+            ctx.accounts.pool_token_account.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            mint,
+            ctx.accounts.treasury_token_account.as_ref().map(|a| a.to_account_info()),
+            None,
+            ctx.accounts.token_program.to_account_info(),
+            &signer_seeds,
+        )?;
+
+        // The project may receive less than the payout if the
+        // mint charges a transfer fee
+        let received = transfer_tokens(
+            ctx.accounts.pool_token_account.to_account_info(),
+            project_token_account.to_account_info(),
+            ctx.accounts.pool.to_account_info(),
+            mint,
+            ctx.accounts.token_program.to_account_info(),
+            &signer_seeds,
+            adj_payout,
+        )?;
+
+        project.deposit(mint.key(), mint.decimals, received)?;
+        project.exit(&crate::ID)?;
+
+        if accounts[2].owner == &crate::ID {
+            let mut entry = Account::<PoolEntry>::try_from(&accounts[2])?;
+            require!(
+                entry.pool == pool_key && entry.project == project.key(),
+                ProtocolError::InvalidBatch
+            );
+            entry.share = pool_data.project_shares
+                .iter()
+                .find(|p| p.project_key == project.key())
+                .map(|p| p.share_data.share)
+                .unwrap_or_default();
+            entry.claimed += received;
+            entry.exit(&crate::ID)?;
+        }
+
+        emit!(PayoutClaimedEvent {
+            pool: pool_key,
+            project: project.key(),
+            mint: mint.key(),
+            amount: received,
+            protocol_fee: fees.protocol,
+            frontend_fee: fees.frontend,
+            frontend: None,
+        });
+    }
+
+    pool_data.maybe_mark_distributed(current_time)?;
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.pool,
+        pool_data,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct DistributePayouts<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = pool,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        constraint = treasury_token_account.owner == config.treasury()?
+            @ ProtocolError::InvalidTreasury,
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+use crate::state::{
+    config::*,
+    pool::*,
+    project::*,
+};
+
+/// Opts a project in to returning the votes it received
+/// in a cancelled pool to the donors who cast them.
+pub fn enable_vote_refunds(
+    ctx: Context<EnableVoteRefunds>,
+    _project_id: u64,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Refunds)?;
+
+    let payer_key = ctx.accounts.payer.key();
+    let project = &ctx.accounts.project;
+    let pool = &mut ctx.accounts.pool;
+
+    require!(project.admins.contains(&payer_key), ProtocolError::NotAuthorized);
+
+    pool.enable_vote_refunds(&project.key())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    project_id: u64,
+    pool_id: u64,
+)]
+pub struct EnableVoteRefunds<'info> {
+    #[account(
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,131 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::events::SubscriptionPaymentEvent;
+use crate::state::{
+    config::*,
+    project::*,
+    receipt::*,
+    subscription::*,
+};
+use crate::util::transfer_tokens;
+
+/// Executes a due payment from a `Subscription` into the project's
+/// token account, recording it on the donor's `ContributionReceipt`
+/// like any other contribution. This is permissionless, so anyone
+/// can crank it.
+pub fn execute_subscription(ctx: Context<ExecuteSubscription>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Subscriptions)?;
+
+    // Mints can be removed, or restricted, after the subscription was created
+    ctx.accounts.config.check_mint(&ctx.accounts.mint.to_account_info())?;
+
+    let subscription = &mut ctx.accounts.subscription;
+    let project = &mut ctx.accounts.project;
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    // Campaigns stop taking payments at their deadline
+    project.can_contribute()?;
+
+    subscription.record_payment(current_time)?;
+
+    let bump = subscription.bump.to_le_bytes();
+    let project_key = subscription.project;
+    let donor_key = subscription.donor;
+
+    let seeds = vec![
+        Subscription::SEED_PREFIX.as_bytes(),
+        project_key.as_ref(),
+        donor_key.as_ref(),
+        &bump,
+    ];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    // The project may receive less than the payment
+    // if the mint charges a transfer fee
+    let received = transfer_tokens(
+        ctx.accounts.donor_token_account.to_account_info(),
+        ctx.accounts.project_token_account.to_account_info(),
+        subscription.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        subscription.amount,
+    )?;
+
+    // Increment fields
+    project.deposit(subscription.mint, ctx.accounts.mint.decimals, received)?;
+    project.refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts);
+    if subscription.payments == 1 {
+        project.contributors += 1;
+    }
+    ctx.accounts.receipt.amount += received;
+
+    emit!(SubscriptionPaymentEvent {
+        subscription: subscription.key(),
+        donor: donor_key,
+        project: project_key,
+        mint: subscription.mint,
+        amount: received,
+        payment_number: subscription.payments,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Subscription::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+            subscription.donor.as_ref(),
+        ],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        mut,
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project.project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        seeds = [
+            ContributionReceipt::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+            subscription.donor.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump = receipt.bump,
+    )]
+    pub receipt: Account<'info, ContributionReceipt>,
+    #[account(address = subscription.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = subscription.donor,
+    )]
+    pub donor_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = project,
+    )]
+    pub project_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
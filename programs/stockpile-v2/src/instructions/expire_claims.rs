@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::ProtocolError;
+use crate::events::ClaimsExpiredEvent;
+use crate::state::{
+    config::*,
+    pool::*,
+    source::Earmark,
+};
+use crate::util::{set_pool_and_maybe_realloc, transfer_tokens};
+
+/// Forfeits the payouts left unclaimed after a pool's claim deadline,
+/// and settles them according to its `UnclaimedPolicy`. Redistributed
+/// payouts are claimed as top-ups by the projects that did claim,
+/// and returned payouts are withdrawn by funders as unspent. Rolled
+/// over payouts are moved into the linked pool here, in every mint at
+/// once, so its accounts are required. Other mints the pool was funded
+/// in are passed as remaining accounts, three per mint: the mint, the
+/// pool's token account and the linked pool's token account. If the
+/// linked pool can no longer be funded, the payouts are returned to
+/// funders instead. Anyone can call this once the deadline has passed.
+pub fn expire_claims<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExpireClaims<'info>>,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Withdrawals)?;
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let pool_key = ctx.accounts.pool.key();
+    let mut pool_data = ctx.accounts.pool.clone().into_inner();
+    let forfeited = pool_data.expire_claims(current_time)?;
+
+    let mut rolled_over_to = None;
+    let fate = pool_data.unclaimed.as_ref().map(|u| u.fate.clone());
+    if let Some(UnclaimedFate::Rollover(target)) = fate {
+        let target_pool = ctx.accounts.target_pool
+            .as_mut()
+            .ok_or(ProtocolError::MissingRolloverPool)?;
+        require!(target_pool.key() == target, ProtocolError::MissingRolloverPool);
+
+        if target_pool.can_fund().is_err() {
+            pool_data.return_unclaimed_to_funders();
+        } else {
+            let bump = pool_data.bump.to_le_bytes();
+            let id_ref = pool_data.pool_id.to_le_bytes();
+            let seeds = vec![Pool::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
+            let signer_seeds = vec![seeds.as_slice()];
+
+            let mut target_data = target_pool.clone().into_inner();
+            for mint_key in pool_data.funding_mints() {
+                let amount = pool_data.take_forfeited(&mint_key)?;
+                if amount == 0 {
+                    continue;
+                }
+
+                let (mint, pool_token_account, target_pool_token_account) =
+                    if mint_key == ctx.accounts.mint.key() {
+                        let target_pool_token_account = ctx.accounts.target_pool_token_account
+                            .as_ref()
+                            .ok_or(ProtocolError::MissingRolloverPool)?;
+                        (
+                            ctx.accounts.mint.clone(),
+                            ctx.accounts.pool_token_account.clone(),
+                            target_pool_token_account.clone(),
+                        )
+                    } else {
+                        let accounts = ctx.remaining_accounts
+                            .chunks_exact(3)
+                            .find(|a| a[0].key() == mint_key)
+                            .ok_or(ProtocolError::PoolVaultMissing)?;
+                        (
+                            InterfaceAccount::<Mint>::try_from(&accounts[0])?,
+                            InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?,
+                            InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?,
+                        )
+                    };
+                require!(
+                    pool_token_account.mint == mint_key && pool_token_account.owner == pool_key,
+                    ProtocolError::PoolVaultMissing
+                );
+                require!(
+                    target_pool_token_account.mint == mint_key
+                        && target_pool_token_account.owner == target,
+                    ProtocolError::MissingRolloverPool
+                );
+
+                let received = transfer_tokens(
+                    pool_token_account.to_account_info(),
+                    target_pool_token_account.to_account_info(),
+                    ctx.accounts.pool.to_account_info(),
+                    &mint,
+                    ctx.accounts.token_program.to_account_info(),
+                    &signer_seeds,
+                    amount,
+                )?;
+
+                // The expired pool is recorded as a funder of the
+                // target, for what arrived after any transfer fee
+                target_data.record_funding(
+                    pool_key,
+                    Some(mint_key),
+                    received,
+                    Earmark::Unrestricted,
+                )?;
+            }
+            set_pool_and_maybe_realloc(
+                target_pool,
+                target_data,
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            )?;
+            rolled_over_to = Some(target);
+        }
+    }
+
+    pool_data.maybe_mark_distributed(current_time)?;
+    ctx.accounts.pool.set_inner(pool_data);
+
+    emit!(ClaimsExpiredEvent {
+        pool: pool_key,
+        forfeited,
+        rolled_over_to,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct ExpireClaims<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = pool,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Only required when payouts are rolled over
+    #[account(mut)]
+    pub target_pool: Option<Box<Account<'info, Pool>>>,
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub target_pool_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{state::pool::*, error::ProtocolError};
+use crate::{state::{config::*, pool::*}, error::ProtocolError};
 
 /// Extends the duration of the pool by
 /// updating it's end field with a new timestamp.
@@ -10,15 +10,21 @@ pub fn extend_pool_duration(
     _pool_id: u64,
     new_end_date: u64,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
     let pool = &mut ctx.accounts.pool;
     let payer = &ctx.accounts.payer;
 
     require!(pool.admins.contains(&payer.key()), ProtocolError::NotAuthorized);
 
+    // An ended round can't be reopened by pushing its end date out
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    require!(
+        matches!(pool.state_at(current_time), PoolState::PendingStart | PoolState::Active),
+        ProtocolError::EndDatePassed
+    );
+
     pool.extend_pool_duration(new_end_date)
-    .expect("Failed to update end date.");
-    
-    Ok(())
 }
 
 #[derive(Accounts)]
@@ -38,5 +44,10 @@ pub struct ExtendPool<'info> {
     pub pool: Account<'info, Pool>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::*, pool::*}, error::ProtocolError};
+
+/// Finalizes a pool whose round has ended, freezing
+/// its shares so projects can claim their payouts.
+pub fn finalize_pool(
+    ctx: Context<FinalizePool>,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let payer = &ctx.accounts.payer;
+
+    require!(pool.admins.contains(&payer.key()), ProtocolError::NotAuthorized);
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    pool.finalize(current_time)?;
+
+    // A pool with nothing to claim is distributed right away
+    pool.maybe_mark_distributed(current_time)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct FinalizePool<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
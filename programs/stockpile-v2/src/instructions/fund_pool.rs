@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token, associated_token};
+use anchor_spl::{associated_token, token_interface::{Mint, TokenAccount, TokenInterface}};
 
-use crate::state::pool::*;
-use crate::util::{mint_is_supported, set_and_maybe_realloc, to_pubkey, USDC_DEVNET_MINT};
+use crate::error::ProtocolError;
+use crate::state::{
+    config::*,
+    pool::*,
+    source::*,
+};
+use crate::util::{set_pool_and_maybe_realloc, transfer_tokens};
 
 /// Funds a pool from a `Source` with an SPL Token.
 /// Pools can be funded at any time, they don't have to be active.
@@ -12,52 +17,46 @@ pub fn fund_pool(
     _pool_id: u64,
     amount: u64,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Funding)?;
+
     // Check to make sure the token is supported
-    mint_is_supported(&ctx.accounts.mint.key())?;
+    ctx.accounts.config.check_mint(&ctx.accounts.mint.to_account_info())?;
 
     // Check to make sure the pool is not closed
     ctx.accounts.pool.can_fund()?;
 
-    // Record the funder
-    // First create a ticket
-    let ticket = FundingTicket::new(
-        ctx.accounts.payer.key(),
-        Some(ctx.accounts.mint.key()),
+    // Perform transfer. Only what arrives after any
+    // transfer fee is counted towards the pool.
+    let amount = transfer_tokens(
+        ctx.accounts.payer_token_account.to_account_info(),
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &[],
         amount,
-    );
-    
-    // Determine new account size with new ticket
-    let mut pool_data: Pool = ctx.accounts.pool.clone().into_inner();
-    pool_data.funders.push(ticket);
-    set_and_maybe_realloc(
-        &mut ctx.accounts.pool, 
-        &pool_data, 
-        ctx.accounts.payer.to_account_info(), 
-        ctx.accounts.system_program.to_account_info(),
     )?;
 
-    // Perform transfer
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.payer_token_account.to_account_info(),
-                to: ctx.accounts.pool_token_account.to_account_info(),
-                authority: ctx.accounts.payer.to_account_info(),
-            },
-        ),
+    // Debit the source's commitment
+    let source = &mut ctx.accounts.source;
+    source.deposit(amount)?;
+
+    // Record the funder against the source, and
+    // determine new account size with the ticket
+    let mut pool_data: Pool = ctx.accounts.pool.clone().into_inner();
+    pool_data.record_funding(
+        source.key(),
+        Some(ctx.accounts.mint.key()),
         amount,
+        source.earmark.clone(),
+    )?;
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.pool,
+        pool_data,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
     )?;
 
-    ctx.accounts.pool.funders.push(
-        FundingTicket::new(
-            ctx.accounts.payer.key(),
-            Some(to_pubkey(USDC_DEVNET_MINT)), 
-            amount,
-    ));
-
-    ctx.accounts.pool.total_funding += amount;
-    
     Ok(())
 }
 
@@ -68,7 +67,7 @@ pub fn fund_pool(
     _amount: u64, // Anchor barfs if you don't have all ix args
 )]
 pub struct FundPool<'info> {
-    #[account( 
+    #[account(
         mut,
         seeds = [
             Pool::SEED_PREFIX.as_bytes(),
@@ -77,22 +76,41 @@ pub struct FundPool<'info> {
         bump = pool.bump,
     )]
     pub pool: Account<'info, Pool>,
-    pub mint: Account<'info, token::Mint>,
+    #[account(
+        mut,
+        seeds = [
+            FundingSource::SEED_PREFIX.as_bytes(),
+            source.name.as_ref(),
+            source.pool_id.to_le_bytes().as_ref(),
+            source.amount.to_le_bytes().as_ref(),
+            source.authority.as_ref(),
+        ],
+        bump = source.bump,
+        constraint = source.pool_id == pool_id @ ProtocolError::SourceMismatch,
+        constraint = source.authority == payer.key() @ ProtocolError::NotAuthorized,
+    )]
+    pub source: Account<'info, FundingSource>,
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = pool,
     )]
-    pub pool_token_account: Account<'info, token::TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = payer,
     )]
-    pub payer_token_account: Account<'info, token::TokenAccount>,
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, token::Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
 }
\ No newline at end of file
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+use crate::program::StockpileV2;
+use crate::state::config::*;
+
+/// Creates the `ProtocolConfig`, with the signer as the protocol
+/// authority. Only the program's upgrade authority can call this,
+/// so the config can't be claimed by someone else after a deploy.
+/// Voters need a Civic pass from `gatekeeper_network`.
+pub fn initialize_config(ctx: Context<InitializeConfig>, gatekeeper_network: Pubkey) -> Result<()> {
+    let mut config = ProtocolConfig::new(
+        ctx.accounts.payer.key(),
+        *ctx.bumps
+            .get("treasury")
+            .expect("Failed to derive bump for `treasury`"),
+        *ctx.bumps
+            .get("config")
+            .expect("Failed to derive bump for `config`"),
+    );
+    config.gatekeeper_network = gatekeeper_network;
+    ctx.accounts.config.set_inner(config);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        space = ProtocolConfig::SPACE,
+        payer = payer,
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// CHECK: Owns the treasury token accounts, holds no data
+    #[account(
+        seeds = [ProtocolConfig::TREASURY_SEED_PREFIX.as_bytes()],
+        bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, StockpileV2>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ ProtocolError::NotAuthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -3,24 +3,30 @@ use anchor_lang::prelude::*;
 use crate::error::ProtocolError;
 
 use crate::state::{
+    config::*,
     pool::*,
     project::*,
+    registry::*,
 };
+use crate::util::{set_and_maybe_realloc, set_pool_and_maybe_realloc};
 
 // Adds a fundraiser into a funding round.
 // This is subject to stipulations depending on the pool config.
 // i.e: Some pools will require manual approval.
-pub fn join_pool(ctx: Context<JoinPool>, _project_id: u64, _pool_id: u64) -> Result<()> {
+pub fn join_pool(ctx: Context<JoinPool>, _project_id: u64, _pool_id: u64, category: Option<u8>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
     let payer_key = ctx.accounts.payer.key();
     let project = &ctx.accounts.project;
     let project_key = ctx.accounts.project.key();
-    let pool = &mut ctx.accounts.pool;
-    let pool_data = pool.clone().into_inner();
+    let mut pool_data = ctx.accounts.pool.clone().into_inner();
 
     // Fundraiser access control check
     require!(project.admins.contains(&payer_key), ProtocolError::NotAuthorized);
 
-    pool.can_fund()?;
+    project.can_join_pool()?;
+
+    pool_data.can_fund()?;
 
     // Check to make sure the fundraiser isnt already in the pool
     if pool_data.project_shares.iter().any(|p| p.project_key == project_key) {
@@ -29,8 +35,13 @@ pub fn join_pool(ctx: Context<JoinPool>, _project_id: u64, _pool_id: u64) -> Res
 
     // Check PoolAccess config
     if pool_data.pool_access == PoolAccess::Open {
-        pool.add_participant(project_key)
-            .expect("Failed to add project.");
+        pool_data.add_participant(project_key, category)?;
+        set_pool_and_maybe_realloc(
+            &mut ctx.accounts.pool,
+            pool_data,
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
 
         msg!(
             "Fundraiser successfully entered with data: {:?}", 
@@ -43,6 +54,38 @@ pub fn join_pool(ctx: Context<JoinPool>, _project_id: u64, _pool_id: u64) -> Res
         return Err(ProtocolError::PoolClosed.into());
     }
 
+    // Record the entry, and index the pool under the project
+    let pool_key = ctx.accounts.pool.key();
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    ctx.accounts.entry.set_inner(
+        PoolEntry::new(
+            pool_key,
+            project_key,
+            category,
+            current_time,
+            *ctx.bumps
+                .get("entry")
+                .expect("Failed to derive bump for `entry`"),
+        )
+    );
+    let mut project_pools = ctx.accounts.project_pools.clone().into_inner();
+    if project_pools.project == Pubkey::default() {
+        project_pools = ProjectPools::new(
+            project_key,
+            *ctx.bumps
+                .get("project_pools")
+                .expect("Failed to derive bump for `project_pools`"),
+        );
+    }
+    project_pools.pools.push(pool_key);
+    set_and_maybe_realloc(
+        &mut ctx.accounts.project_pools,
+        &project_pools,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    ctx.accounts.project_pools.set_inner(project_pools);
+
     Ok(())
 }
 
@@ -69,7 +112,35 @@ pub struct JoinPool<'info> {
         bump = project.bump,
     )]
     pub project: Account<'info, Project>,
+    #[account(
+        init,
+        space = PoolEntry::SPACE,
+        payer = payer,
+        seeds = [
+            PoolEntry::SEED_PREFIX.as_bytes(),
+            pool.key().as_ref(),
+            project.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub entry: Account<'info, PoolEntry>,
+    #[account(
+        init_if_needed,
+        space = ProjectPools::SPACE,
+        payer = payer,
+        seeds = [
+            ProjectPools::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub project_pools: Account<'info, ProjectPools>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    config::*,
+    legacy::*,
+    milestone::*,
+};
+
+/// Rewrites a `Milestone` from its legacy layout into the current one.
+/// Its status and percentage are carried over unchanged.
+pub fn migrate_milestone(ctx: Context<MigrateMilestone>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Migrations)?;
+
+    let account_info = ctx.accounts.milestone.to_account_info();
+    let milestone: Milestone = migrate_account_data(&account_info.try_borrow_data()?)?;
+
+    write_migrated_account(
+        &account_info,
+        &milestone,
+        Milestone::SPACE,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )
+}
+
+#[derive(Accounts)]
+pub struct MigrateMilestone<'info> {
+    /// CHECK: Checked against the current and legacy layouts in the instruction
+    #[account(mut, owner = crate::ID)]
+    pub milestone: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
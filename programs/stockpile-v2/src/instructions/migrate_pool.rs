@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::error::ProtocolError;
+use crate::state::{
+    config::*,
+    legacy::*,
+    pool::*,
+    source::*,
+};
+
+/// Rewrites a `Pool` from its legacy layout into the current one.
+/// Legacy pools that were funded need the pool's vault, and every
+/// funder's `FundingSource` for the pool as remaining accounts, so
+/// their tickets can be moved onto them. Only the pool's admins
+/// can say which vault that was.
+pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Migrations)?;
+
+    let account_info = ctx.accounts.pool.to_account_info();
+    let mut pool: Pool = migrate_account_data(&account_info.try_borrow_data()?)?;
+
+    if !pool.funders.is_empty() {
+        require!(
+            pool.admins.contains(&ctx.accounts.payer.key()),
+            ProtocolError::NotAuthorized
+        );
+        let vault = ctx.accounts.pool_token_account
+            .as_ref()
+            .ok_or(ProtocolError::PoolVaultMissing)?;
+        ctx.accounts.config.mint_is_supported(&vault.mint)?;
+        require!(vault.amount >= pool.balance, ProtocolError::PoolVaultMissing);
+
+        let mut sources = vec![];
+        for source_info in ctx.remaining_accounts.iter() {
+            require!(source_info.owner == &crate::ID, ProtocolError::SourceMismatch);
+            let source = FundingSource::try_deserialize(&mut &source_info.try_borrow_data()?[..])?;
+            // Sources have to be migrated first
+            require!(source.version == FundingSource::VERSION, ProtocolError::AccountNotMigratable);
+            sources.push((source_info.key(), source));
+        }
+
+        rebind_legacy_funders(&mut pool, vault.mint, &sources)?;
+    }
+
+    write_migrated_account(
+        &account_info,
+        &pool,
+        Pool::SPACE,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    /// CHECK: Checked against the current and legacy layouts in the instruction
+    #[account(mut, owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+    #[account(token::authority = pool)]
+    pub pool_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
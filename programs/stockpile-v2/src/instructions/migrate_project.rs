@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::error::ProtocolError;
+use crate::state::{
+    config::*,
+    legacy::*,
+    project::*,
+};
+
+/// Rewrites a `Project` from its legacy layout into the current one.
+/// Legacy projects that hold funds need the mint they were raised
+/// in, so their balance can be carried into a mint balance. Only
+/// the project's admins can say which mint that was.
+pub fn migrate_project(ctx: Context<MigrateProject>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Migrations)?;
+
+    let account_info = ctx.accounts.project.to_account_info();
+    let mut project: Project = migrate_account_data(&account_info.try_borrow_data()?)?;
+
+    if project.raised > 0 || project.balance > 0 {
+        require!(
+            project.admins.contains(&ctx.accounts.payer.key()),
+            ProtocolError::NotAuthorized
+        );
+        let mint = ctx.accounts.mint.as_ref().ok_or(ProtocolError::MintNotSupported)?;
+        ctx.accounts.config.mint_is_supported(&mint.key())?;
+        project.balances.push(MintBalance {
+            mint: mint.key(),
+            decimals: mint.decimals,
+            raised: project.raised,
+            balance: project.balance,
+        });
+    }
+
+    write_migrated_account(
+        &account_info,
+        &project,
+        Project::SPACE,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )
+}
+
+#[derive(Accounts)]
+pub struct MigrateProject<'info> {
+    /// CHECK: Checked against the current and legacy layouts in the instruction
+    #[account(mut, owner = crate::ID)]
+    pub project: UncheckedAccount<'info>,
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
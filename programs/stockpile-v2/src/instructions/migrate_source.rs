@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    config::*,
+    legacy::*,
+    source::*,
+};
+
+/// Rewrites a `FundingSource` from its legacy layout into the current
+/// one. Legacy sources didn't track deposits, so their whole commitment
+/// is taken as deposited. Migrate sources before the pools they fund.
+pub fn migrate_source(ctx: Context<MigrateSource>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Migrations)?;
+
+    let account_info = ctx.accounts.source.to_account_info();
+    let source: FundingSource = migrate_account_data(&account_info.try_borrow_data()?)?;
+
+    write_migrated_account(
+        &account_info,
+        &source,
+        FundingSource::SPACE,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )
+}
+
+#[derive(Accounts)]
+pub struct MigrateSource<'info> {
+    /// CHECK: Checked against the current and legacy layouts in the instruction
+    #[account(mut, owner = crate::ID)]
+    pub source: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
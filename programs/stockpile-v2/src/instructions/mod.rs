@@ -19,6 +19,40 @@ pub mod withdraw_funds_from_round;
 pub mod update_project;
 pub mod extend_pool_duration;
 pub mod update_pool;
+pub mod create_subscription;
+pub mod execute_subscription;
+pub mod update_subscription;
+pub mod cancel_subscription;
+pub mod refund_contribution;
+pub mod refresh_project_value;
+pub mod finalize_pool;
+pub mod cancel_pool;
+pub mod enable_vote_refunds;
+pub mod refund_vote;
+pub mod reschedule_pool;
+pub mod close_pool_early;
+pub mod migrate_pool;
+pub mod migrate_project;
+pub mod migrate_milestone;
+pub mod migrate_source;
+pub mod close_source;
+pub mod close_pool;
+pub mod initialize_config;
+pub mod update_config;
+pub mod withdraw_fees;
+pub mod prune_directory;
+pub mod view_pool;
+pub mod view_project_share;
+pub mod view_marginal_match;
+pub mod contribute_batch;
+pub mod distribute_payouts;
+pub mod expire_claims;
+pub mod create_series;
+pub mod update_series;
+pub mod spawn_round;
+pub mod rollover_unspent;
+pub mod withdraw_rollover_from_round;
+pub mod carry_over_project;
 
 pub use add_project::*;
 pub use close_milestone::*;
@@ -41,3 +75,37 @@ pub use withdraw_funds_from_round::*;
 pub use update_project::*;
 pub use update_pool::*;
 pub use extend_pool_duration::*;
+pub use create_subscription::*;
+pub use execute_subscription::*;
+pub use update_subscription::*;
+pub use cancel_subscription::*;
+pub use refund_contribution::*;
+pub use refresh_project_value::*;
+pub use finalize_pool::*;
+pub use cancel_pool::*;
+pub use enable_vote_refunds::*;
+pub use refund_vote::*;
+pub use reschedule_pool::*;
+pub use close_pool_early::*;
+pub use migrate_pool::*;
+pub use migrate_project::*;
+pub use migrate_milestone::*;
+pub use migrate_source::*;
+pub use close_source::*;
+pub use close_pool::*;
+pub use initialize_config::*;
+pub use update_config::*;
+pub use withdraw_fees::*;
+pub use prune_directory::*;
+pub use view_pool::*;
+pub use view_project_share::*;
+pub use view_marginal_match::*;
+pub use contribute_batch::*;
+pub use distribute_payouts::*;
+pub use expire_claims::*;
+pub use create_series::*;
+pub use update_series::*;
+pub use spawn_round::*;
+pub use rollover_unspent::*;
+pub use withdraw_rollover_from_round::*;
+pub use carry_over_project::*;
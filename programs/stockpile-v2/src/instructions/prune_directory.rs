@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    config::*,
+    pool::*,
+    registry::*,
+};
+
+/// Removes pools that are no longer active from a page of the
+/// directory. The pools to check are passed in as remaining
+/// accounts, and anyone can call this to keep the directory tidy.
+pub fn prune_directory<'info>(
+    ctx: Context<'_, '_, '_, 'info, PruneDirectory<'info>>,
+    _page: u32,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let mut inactive = vec![];
+
+    for info in ctx.remaining_accounts.iter() {
+        // Closed pools have been handed back to the system program
+        let is_active = if info.owner != &crate::ID || info.data_is_empty() {
+            false
+        } else {
+            let pool = Account::<Pool>::try_from(info)?;
+            matches!(pool.state_at(current_time), PoolState::PendingStart | PoolState::Active)
+        };
+        if !is_active {
+            inactive.push(info.key());
+        }
+    }
+
+    ctx.accounts.directory.pools.retain(|p| !inactive.contains(p));
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    page: u32,
+)]
+pub struct PruneDirectory<'info> {
+    #[account(
+        mut,
+        seeds = [
+            PoolDirectory::SEED_PREFIX.as_bytes(),
+            page.to_le_bytes().as_ref(),
+        ],
+        bump = directory.bump,
+    )]
+    pub directory: Account<'info, PoolDirectory>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::state::project::*;
+use crate::state::config::*;
+
+/// Recalculates the USD value of a project's balances from
+/// the oracle, settling an all-or-nothing campaign whose deadline
+/// has passed. Permissionless, with the oracle accounts for
+/// each mint passed in as remaining accounts.
+pub fn refresh_project_value(ctx: Context<RefreshProjectValue>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Projects)?;
+
+    let project = &mut ctx.accounts.project;
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    project.settle_campaign(current_time, &ctx.accounts.config.oracle(), ctx.remaining_accounts)?;
+    project.try_refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts)?;
+
+    msg!("Project raised {:?}, balance {:?}", project.raised, project.balance);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefreshProjectValue<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project.project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
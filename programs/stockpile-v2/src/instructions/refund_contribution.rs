@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::ProtocolError;
+use crate::state::{
+    config::*,
+    project::*,
+    receipt::*,
+};
+use crate::util::transfer_tokens;
+
+/// Refunds a donor's contributions to an all-or-nothing
+/// campaign that missed its goal by the deadline.
+pub fn refund_contribution(ctx: Context<RefundContribution>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Refunds)?;
+
+    let project = &mut ctx.accounts.project;
+    let receipt = &mut ctx.accounts.receipt;
+
+    // Oracle accounts are passed in as remaining accounts. Campaigns
+    // are settled on their value the first time they're checked
+    // after the deadline
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    project.settle_campaign(current_time, &ctx.accounts.config.oracle(), ctx.remaining_accounts)?;
+    project.can_refund(current_time)?;
+
+    let refund = receipt.amount;
+    require!(refund > 0, ProtocolError::NothingToRefund);
+
+    let bump = project.bump.to_le_bytes();
+    let id_ref = project.project_id.to_le_bytes();
+
+    let seeds = vec![Project::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    transfer_tokens(
+        ctx.accounts.project_token_account.to_account_info(),
+        ctx.accounts.payer_token_account.to_account_info(),
+        project.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        refund,
+    )?;
+
+    receipt.amount = 0;
+    project.refund(&receipt.mint, refund)?;
+    project.refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefundContribution<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project.project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        seeds = [
+            ContributionReceipt::SEED_PREFIX.as_bytes(),
+            project.key().as_ref(),
+            payer.key().as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump = receipt.bump,
+    )]
+    pub receipt: Account<'info, ContributionReceipt>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = project,
+    )]
+    pub project_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
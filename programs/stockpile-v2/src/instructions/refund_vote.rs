@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{
+    config::*,
+    pool::*,
+    project::*,
+};
+use crate::util::transfer_tokens;
+
+/// Returns a donor's votes for a project in a cancelled pool,
+/// once the project has enabled vote refunds.
+pub fn refund_vote(
+    ctx: Context<RefundVote>,
+    _project_id: u64,
+    _pool_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Refunds)?;
+
+    let payer_key = ctx.accounts.payer.key();
+    let mint_key = ctx.accounts.mint.key();
+    let project = &mut ctx.accounts.project;
+    let pool = &mut ctx.accounts.pool;
+
+    let refund = pool.take_vote_refund(&project.key(), &payer_key, &mint_key)?;
+
+    msg!("Refunding {:?} in votes to {:?}", refund, payer_key);
+
+    let bump = project.bump.to_le_bytes();
+    let id_ref = project.project_id.to_le_bytes();
+
+    let seeds = vec![Project::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    transfer_tokens(
+        ctx.accounts.project_token_account.to_account_info(),
+        ctx.accounts.payer_token_account.to_account_info(),
+        project.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        refund,
+    )?;
+
+    project.refund(&mint_key, refund)?;
+    project.refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    project_id: u64,
+    pool_id: u64,
+)]
+pub struct RefundVote<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = project,
+    )]
+    pub project_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = payer,
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::*, pool::*}, error::ProtocolError};
+
+/// Moves the start and end of a pool that hasn't started yet.
+/// The new schedule must start in the future and run between
+/// the minimum and maximum pool durations.
+pub fn reschedule_pool(
+    ctx: Context<ReschedulePool>,
+    _pool_id: u64,
+    new_start: u64,
+    new_end: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let pool = &mut ctx.accounts.pool;
+    let payer = &ctx.accounts.payer;
+
+    require!(pool.admins.contains(&payer.key()), ProtocolError::NotAuthorized);
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    pool.reschedule(new_start, new_end, current_time)
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+    _new_start: u64,
+    _new_end: u64,
+)]
+pub struct ReschedulePool<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::ProtocolError;
+use crate::events::UnspentRolledOverEvent;
+use crate::state::{
+    config::*,
+    pool::*,
+    series::*,
+    source::*,
+};
+use crate::util::{set_pool_and_maybe_realloc, transfer_tokens};
+
+/// Rolls the matching a series' previous round couldn't pay out into
+/// its current round. The previous round's funders are passed as
+/// remaining accounts: either the `FundingSource`s that deposited
+/// into it, or the pools whose leftovers were rolled into it. What's
+/// rolled over counts as refunded, so it can't be withdrawn again.
+/// Anyone can call this once the previous round is finalized.
+pub fn rollover_unspent<'info>(
+    ctx: Context<'_, '_, '_, 'info, RolloverUnspent<'info>>,
+    _series_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Funding)?;
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let series = &ctx.accounts.series;
+    let previous_key = ctx.accounts.previous_pool.key();
+    let mut previous = ctx.accounts.previous_pool.clone().into_inner();
+
+    require!(
+        series.previous_pool_id == Some(previous.pool_id)
+            && series.current_pool_id == Some(ctx.accounts.current_pool.pool_id),
+        ProtocolError::SeriesPoolMismatch
+    );
+    require!(
+        matches!(
+            previous.state_at(current_time),
+            PoolState::Finalized | PoolState::Distributed
+        ),
+        ProtocolError::PoolNotFinalized
+    );
+    ctx.accounts.current_pool.can_fund()?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let mut amount: u64 = 0;
+    for account in ctx.remaining_accounts.iter() {
+        if let Ok(mut source) = Account::<FundingSource>::try_from(account) {
+            require!(source.pool_id == previous.pool_id, ProtocolError::SourceMismatch);
+            let unspent = previous
+                .calculate_unspent_for_source(&source.key(), &mint_key)
+                .saturating_sub(source.refunded_in(&mint_key));
+            source.record_refund(mint_key, unspent)?;
+            source.exit(&crate::ID)?;
+            amount += unspent;
+        } else {
+            // Pools that rolled matching in have no `FundingSource`
+            let funder = Account::<Pool>::try_from(account)?;
+            amount += previous.take_rolled_in_unspent(&funder.key(), &mint_key);
+        }
+    }
+
+    require!(amount > 0, ProtocolError::NothingToRefund);
+
+    let bump = previous.bump.to_le_bytes();
+    let id_ref = previous.pool_id.to_le_bytes();
+    let seeds = vec![Pool::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    let received = transfer_tokens(
+        ctx.accounts.previous_pool_token_account.to_account_info(),
+        ctx.accounts.current_pool_token_account.to_account_info(),
+        ctx.accounts.previous_pool.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        amount,
+    )?;
+
+    ctx.accounts.previous_pool.set_inner(previous);
+
+    // The previous round is recorded as a funder of the current
+    // one, for what arrived after any transfer fee
+    let mut current = ctx.accounts.current_pool.clone().into_inner();
+    current.record_funding(
+        previous_key,
+        Some(ctx.accounts.mint.key()),
+        received,
+        Earmark::Unrestricted,
+    )?;
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.current_pool,
+        current,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    emit!(UnspentRolledOverEvent {
+        series: ctx.accounts.series.key(),
+        from: previous_key,
+        to: ctx.accounts.current_pool.key(),
+        amount: received,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    series_id: u64,
+)]
+pub struct RolloverUnspent<'info> {
+    #[account(
+        seeds = [
+            RoundSeries::SEED_PREFIX.as_bytes(),
+            series_id.to_le_bytes().as_ref(),
+        ],
+        bump = series.bump,
+    )]
+    pub series: Box<Account<'info, RoundSeries>>,
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            previous_pool.pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = previous_pool.bump,
+    )]
+    pub previous_pool: Box<Account<'info, Pool>>,
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            current_pool.pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = current_pool.bump,
+    )]
+    pub current_pool: Box<Account<'info, Pool>>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = previous_pool,
+    )]
+    pub previous_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = current_pool,
+    )]
+    pub current_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+use crate::events::{PoolCreatedEvent, RoundSpawnedEvent};
+use crate::state::{
+    config::*,
+    pool::*,
+    registry::*,
+    series::*,
+};
+use crate::util::set_pool_and_maybe_realloc;
+
+/// Spawns the next round of a series from its template. The
+/// previous round has to have ended, and payouts left unclaimed
+/// in it are pointed at the new round. Anyone can call this, and
+/// the caller pays the new pool's rent.
+pub fn spawn_round(
+    ctx: Context<SpawnRound>,
+    _series_id: u64,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    let series = &ctx.accounts.series;
+    series.can_spawn()?;
+
+    if let Some(previous_id) = series.current_pool_id {
+        let previous_pool = ctx.accounts.previous_pool
+            .as_ref()
+            .ok_or(ProtocolError::SeriesPoolMismatch)?;
+        require!(previous_pool.pool_id == previous_id, ProtocolError::SeriesPoolMismatch);
+        require!(
+            !matches!(
+                previous_pool.state_at(current_time),
+                PoolState::PendingStart | PoolState::Active
+            ),
+            ProtocolError::PoolStillActive
+        );
+    }
+
+    ctx.accounts.config.validate_name(&series.round_name())?;
+    let pool_id = ctx.accounts.config.take_pool_id()?;
+    let (start, end) = series.next_round(current_time);
+
+    let mut pool = series.build_pool(
+        pool_id,
+        start,
+        end,
+        *ctx.bumps
+            .get("pool")
+            .expect("Failed to derive bump for `pool`"),
+    )?;
+
+    // New pools price mints with the protocol's feeds
+    let config = &ctx.accounts.config;
+    pool.oracle.feeds = config.mints.clone();
+    pool.oracle.max_staleness = config.limits.max_staleness;
+
+    // The caller's rent is returned to them when the pool is closed
+    let rent = ctx.accounts.pool.to_account_info().lamports();
+    pool.record_rent(ctx.accounts.payer.key(), rent);
+
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.pool,
+        pool,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+    let pool_key = ctx.accounts.pool.key();
+
+    // Unclaimed payouts from the previous round roll into this one
+    if let Some(previous_pool) = &mut ctx.accounts.previous_pool {
+        let mut previous_data = previous_pool.clone().into_inner();
+        previous_data.roll_unclaimed_into(pool_key);
+        set_pool_and_maybe_realloc(
+            previous_pool,
+            previous_data,
+            ctx.accounts.payer.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+
+    // List the pool in the directory, moving on
+    // to a new page once this one is full
+    let directory = &mut ctx.accounts.directory;
+    if directory.version == 0 {
+        directory.set_inner(
+            PoolDirectory::new(
+                ctx.accounts.config.directory_page,
+                *ctx.bumps
+                    .get("directory")
+                    .expect("Failed to derive bump for `directory`"),
+            )
+        );
+    }
+    if directory.add(pool_key)? {
+        ctx.accounts.config.directory_page += 1;
+    }
+
+    let series = &mut ctx.accounts.series;
+    series.record_round(pool_id, start);
+
+    emit!(PoolCreatedEvent {
+        pool: pool_key,
+        pool_id,
+        creator: series.key(),
+    });
+    emit!(RoundSpawnedEvent {
+        series: series.key(),
+        pool: pool_key,
+        pool_id,
+        round: series.rounds,
+        start,
+        end,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    series_id: u64,
+)]
+pub struct SpawnRound<'info> {
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+    #[account(
+        mut,
+        seeds = [
+            RoundSeries::SEED_PREFIX.as_bytes(),
+            series_id.to_le_bytes().as_ref(),
+        ],
+        bump = series.bump,
+    )]
+    pub series: Box<Account<'info, RoundSeries>>,
+    /// The series' current round, required once it has one
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            previous_pool.pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = previous_pool.bump,
+    )]
+    pub previous_pool: Option<Box<Account<'info, Pool>>>,
+    #[account(
+        init,
+        space = Pool::SPACE,
+        payer = payer,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            config.next_pool_id.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    #[account(
+        init_if_needed,
+        space = PoolDirectory::SPACE,
+        payer = payer,
+        seeds = [
+            PoolDirectory::SEED_PREFIX.as_bytes(),
+            config.directory_page.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub directory: Box<Account<'info, PoolDirectory>>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::config::*, error::ProtocolError};
+
+/// Updates the `ProtocolConfig`, given a specific field.
+/// Not subject to the pause switches, so the
+/// protocol authority can always unpause.
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    update_field: UpdateConfigField,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    match update_field {
+        UpdateConfigField::Authority(new_authority) => config.authority = new_authority,
+        UpdateConfigField::AddMint(mint) => config.add_mint(mint)?,
+        UpdateConfigField::RemoveMint(mint) => config.remove_mint(&mint),
+        UpdateConfigField::Limits(new_limits) => {
+            new_limits.validate()?;
+            config.limits = new_limits;
+        },
+        UpdateConfigField::Pause(paused) => config.paused = paused,
+        UpdateConfigField::PauseFeature(feature, paused) => config.set_feature_paused(feature, paused),
+        UpdateConfigField::SkipIds(next_project_id, next_pool_id) => {
+            config.skip_ids(next_project_id, next_pool_id)?
+        },
+        UpdateConfigField::Fees(new_fees) => {
+            new_fees.validate()?;
+            config.fees = new_fees;
+        },
+        UpdateConfigField::AllowExtensions(mint, extensions) => {
+            config.allow_extensions(mint, &extensions)?
+        },
+        UpdateConfigField::GatekeeperNetwork(new_network) => config.gatekeeper_network = new_network,
+    };
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+        has_one = authority @ ProtocolError::NotAuthorized,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub authority: Signer<'info>,
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{state::pool::*, error::ProtocolError};
+use crate::{state::{config::*, pool::*}, error::ProtocolError, events::MetadataUpdatedEvent, util::set_pool_and_maybe_realloc};
 
 /// Updates a pool, given a specific field
 pub fn update_pool(
@@ -8,25 +8,83 @@ pub fn update_pool(
     _pool_id: u64,
     update_field: UpdatePoolField,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
     let payer_key = &mut ctx.accounts.payer.key();
-    let pool = &mut ctx.accounts.pool;
+    let pool_key = ctx.accounts.pool.key();
+    let mut pool = ctx.accounts.pool.clone().into_inner();
 
     require!(pool.admins.contains(&payer_key), ProtocolError::NotAuthorized);
 
     match update_field {
-        UpdatePoolField::Name(new_name) => pool.name = new_name,
+        UpdatePoolField::Name(new_name) => {
+            ctx.accounts.config.validate_name(&new_name)?;
+            pool.name = new_name;
+        },
         UpdatePoolField::AddAdmin(new_admin) => {
             if !pool.admins.contains(&new_admin) {
                 pool.admins.push(new_admin);
+                ctx.accounts.config.validate_admins(&pool.admins)?;
             }
         },
         UpdatePoolField::RemoveAdmin(admin_to_remove) => {
             pool.admins.retain(|&admin| admin != admin_to_remove);
         },
         UpdatePoolField::Approval(new_approval) => pool.pool_access = new_approval,
-        UpdatePoolField::Status(new_status) => pool.pool_state = new_status,
+        UpdatePoolField::Caps(new_caps) => {
+            // Caps can't change once votes are being counted
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            require!(current_time < pool.start, ProtocolError::PoolAlreadyStarted);
+            new_caps.validate()?;
+            pool.caps = new_caps;
+        },
+        UpdatePoolField::AddCategory(category) => {
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            require!(current_time < pool.start, ProtocolError::PoolAlreadyStarted);
+            pool.add_category(category)?;
+        },
+        UpdatePoolField::Oracle(new_oracle) => {
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            require!(current_time < pool.start, ProtocolError::PoolAlreadyStarted);
+            new_oracle.validate()?;
+            pool.oracle = new_oracle;
+        },
+        UpdatePoolField::SoftClose(new_soft_close) => {
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            require!(current_time < pool.start, ProtocolError::PoolAlreadyStarted);
+            if let Some(soft_close) = &new_soft_close {
+                soft_close.validate()?;
+            }
+            pool.soft_close = new_soft_close;
+        },
+        UpdatePoolField::Unclaimed(new_unclaimed) => {
+            // Funders commit to the policy before the round starts
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            require!(current_time < pool.start, ProtocolError::PoolAlreadyStarted);
+            if let Some(policy) = &new_unclaimed {
+                policy.validate(&pool_key)?;
+            }
+            pool.unclaimed = new_unclaimed;
+        },
+        UpdatePoolField::Metadata(uri, hash) => {
+            pool.metadata.update(uri, hash)?;
+            emit!(MetadataUpdatedEvent {
+                account: pool_key,
+                uri: pool.metadata.uri.clone(),
+                hash,
+                revision: pool.metadata.revision,
+            });
+        },
     };
 
+    // Names, admins, categories and metadata can grow the pool
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.pool,
+        pool,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
     Ok(())
 }
 
@@ -46,5 +104,10 @@ pub struct UpdatePool<'info> {
     pub pool: Account<'info, Pool>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
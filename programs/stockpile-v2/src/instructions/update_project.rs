@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{state::project::*, error::ProtocolError};
+use crate::{state::{config::*, project::*}, error::ProtocolError, events::MetadataUpdatedEvent};
 
 /// Updates a project, given a specific field
 pub fn update_project(
@@ -8,17 +8,27 @@ pub fn update_project(
     _project_id: u64,
     update_field: UpdateField,
 ) -> Result<()> {
+    ctx.accounts.config.check(Feature::Projects)?;
+
     let payer_key = &mut ctx.accounts.payer.key();
     let project = &mut ctx.accounts.project;
 
     require!(project.admins.contains(&payer_key), ProtocolError::NotAuthorized);
 
     match update_field {
-        UpdateField::Name(new_name) => project.name = new_name,
-        UpdateField::Goal(new_goal) => project.goal = new_goal,
+        UpdateField::Name(new_name) => {
+            ctx.accounts.config.validate_name(&new_name)?;
+            project.name = new_name;
+        },
+        UpdateField::Goal(new_goal) => {
+            // Donors contributed towards the existing goal
+            require!(!project.has_raised(), ProtocolError::CampaignLocked);
+            project.goal = new_goal;
+        },
         UpdateField::AddAdmin(new_admin) => {
             if !project.admins.contains(&new_admin) {
                 project.admins.push(new_admin);
+                ctx.accounts.config.validate_admins(&project.admins)?;
             }
         },
         UpdateField::RemoveAdmin(admin_to_remove) => {
@@ -26,6 +36,24 @@ pub fn update_project(
         },
         UpdateField::Beneficiary(new_beneficiary) => project.beneficiary = new_beneficiary,
         UpdateField::Status(new_status) => project.status = new_status,
+        UpdateField::Campaign(new_campaign) => {
+            // Donors contributed under the existing terms
+            require!(!project.has_raised(), ProtocolError::CampaignLocked);
+            if let Some(campaign) = &new_campaign {
+                campaign.validate()?;
+            }
+            project.campaign = new_campaign;
+            project.goal_met = None;
+        },
+        UpdateField::Metadata(uri, hash) => {
+            project.metadata.update(uri, hash)?;
+            emit!(MetadataUpdatedEvent {
+                account: project.key(),
+                uri: project.metadata.uri.clone(),
+                hash,
+                revision: project.metadata.revision,
+            });
+        },
     };
 
     Ok(())
@@ -47,5 +75,10 @@ pub struct UpdateProject<'info> {
     pub project: Account<'info, Project>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
 }
\ No newline at end of file
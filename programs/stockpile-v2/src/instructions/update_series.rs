@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ProtocolError, state::{config::*, series::*}};
+
+/// Updates a series, given a specific field. Changes
+/// apply to rounds spawned after the update.
+pub fn update_series(
+    ctx: Context<UpdateSeries>,
+    _series_id: u64,
+    update_field: UpdateSeriesField,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Pools)?;
+
+    let payer_key = ctx.accounts.payer.key();
+    let series = &mut ctx.accounts.series;
+
+    require!(series.admins.contains(&payer_key), ProtocolError::NotAuthorized);
+
+    match update_field {
+        UpdateSeriesField::Name(new_name) => {
+            ctx.accounts.config.validate_name(&new_name)?;
+            RoundSeries::validate_name(&new_name)?;
+            series.name = new_name;
+        },
+        UpdateSeriesField::AddAdmin(new_admin) => {
+            if !series.admins.contains(&new_admin) {
+                series.admins.push(new_admin);
+                ctx.accounts.config.validate_admins(&series.admins)?;
+            }
+        },
+        UpdateSeriesField::RemoveAdmin(admin_to_remove) => {
+            series.admins.retain(|&admin| admin != admin_to_remove);
+        },
+        UpdateSeriesField::Template(new_template) => {
+            new_template.validate()?;
+            series.template = new_template;
+        },
+        UpdateSeriesField::MaxRounds(new_max_rounds) => {
+            if let Some(max_rounds) = new_max_rounds {
+                require!(max_rounds > 0, ProtocolError::InvalidSeries);
+            }
+            series.max_rounds = new_max_rounds;
+        },
+    };
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    _series_id: u64,
+)]
+pub struct UpdateSeries<'info> {
+    #[account(
+        mut,
+        seeds = [
+            RoundSeries::SEED_PREFIX.as_bytes(),
+            _series_id.to_le_bytes().as_ref(),
+        ],
+        bump = series.bump,
+    )]
+    pub series: Account<'info, RoundSeries>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+}
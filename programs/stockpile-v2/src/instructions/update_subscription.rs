@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{config::*, subscription::*}, error::ProtocolError};
+
+/// Updates a subscription, given a specific field
+pub fn update_subscription(
+    ctx: Context<UpdateSubscription>,
+    update_field: UpdateSubscriptionField,
+) -> Result<()> {
+    ctx.accounts.config.check(Feature::Subscriptions)?;
+
+    let subscription = &mut ctx.accounts.subscription;
+
+    require!(
+        subscription.status != SubscriptionStatus::Cancelled,
+        ProtocolError::SubscriptionCancelled
+    );
+
+    match update_field {
+        UpdateSubscriptionField::Pause => subscription.pause()?,
+        UpdateSubscriptionField::Resume => {
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            subscription.resume(current_time)?
+        },
+        UpdateSubscriptionField::Amount(new_amount) => {
+            require!(new_amount > 0, ProtocolError::InvalidSubscription);
+            subscription.amount = new_amount;
+        },
+    };
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateSubscription<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Subscription::SEED_PREFIX.as_bytes(),
+            subscription.project.as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{
+    config::*,
+    pool::*,
+    project::*,
+};
+use crate::util::transfer_fee_on;
+
+/// How much matching a donation would add to a project,
+/// in each mint the pool was funded in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct MarginalMatch {
+    /// The vote that would be recorded, after fees and any transfer fee
+    pub vote: u64,
+    pub fees: u64,
+    pub current_payout: Vec<MintAmount>,
+    pub projected_payout: Vec<MintAmount>,
+    pub marginal_match: Vec<MintAmount>,
+}
+
+/// Returns the `MarginalMatch` for a donation of `amount` from `donor`,
+/// as `contribute_with_vote` would check and record it. Oracle accounts
+/// are passed in as remaining accounts. Nothing is written, so this is
+/// meant to be simulated.
+pub fn view_marginal_match<'info>(
+    ctx: Context<'_, '_, '_, 'info, ViewMarginalMatch<'info>>,
+    _pool_id: u64,
+    _project_id: u64,
+    donor: Pubkey,
+    amount: u64,
+) -> Result<MarginalMatch> {
+    let pool = &ctx.accounts.pool;
+    let project_key = ctx.accounts.project.key();
+    let mint = ctx.accounts.mint.key();
+
+    ctx.accounts.config.check_mint(&ctx.accounts.mint.to_account_info())?;
+    pool.oracle.check_mint(&mint, ctx.accounts.mint.decimals)?;
+
+    let fees = ctx.accounts.config.calculate_fees(
+        FeeKind::Contribution,
+        amount,
+        &[donor, project_key],
+        false,
+    )?;
+    let vote = amount - fees.total();
+
+    // Only what arrives after any transfer fee is recorded
+    let mint_info = ctx.accounts.mint.to_account_info();
+    let vote = vote - transfer_fee_on(&mint_info.try_borrow_data()?, vote, Clock::get()?.epoch)?;
+
+    let mut mints = pool.vote_mints();
+    mints.push(mint);
+    let prices = pool.oracle.load_prices(&mints, ctx.remaining_accounts)?;
+
+    let (current_payout, projected_payout) = pool.simulate_vote(
+        &project_key,
+        donor,
+        mint,
+        vote,
+        &prices,
+    )?;
+
+    let marginal_match = current_payout
+        .iter()
+        .zip(projected_payout.iter())
+        .map(|(current, projected)| MintAmount {
+            mint: projected.mint,
+            amount: projected.amount.saturating_sub(current.amount),
+        })
+        .collect();
+
+    Ok(MarginalMatch {
+        vote,
+        fees: fees.total(),
+        current_payout,
+        projected_payout,
+        marginal_match,
+    })
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+    project_id: u64,
+)]
+pub struct ViewMarginalMatch<'info> {
+    #[account(
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    #[account(
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Box<Account<'info, Project>>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::state::pool::*;
+
+/// A pool's state at the current time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct PoolSummary {
+    pub pool_id: u64,
+    pub state: PoolState,
+    pub start: u64,
+    pub end: u64,
+    pub total_funding: u64,
+    pub balance: u64,
+    pub participants: u32,
+    pub votes: u32,
+    pub extended_by: u64,
+}
+
+/// Returns a `PoolSummary`. Nothing is written,
+/// so this is meant to be simulated.
+pub fn view_pool(ctx: Context<ViewPool>, _pool_id: u64) -> Result<PoolSummary> {
+    let pool = &ctx.accounts.pool;
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    Ok(PoolSummary {
+        pool_id: pool.pool_id,
+        state: pool.state_at(current_time),
+        start: pool.start,
+        end: pool.end,
+        total_funding: pool.total_funding,
+        balance: pool.balance,
+        participants: pool.project_shares.len() as u32,
+        votes: pool.project_shares
+            .iter()
+            .map(|p| p.share_data.votes.len() as u32)
+            .sum(),
+        extended_by: pool.extended_by,
+    })
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+)]
+pub struct ViewPool<'info> {
+    #[account(
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+}
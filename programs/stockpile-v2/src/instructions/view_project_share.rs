@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+use crate::state::{
+    pool::*,
+    project::*,
+};
+
+/// A project's share of a pool's matching
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ProjectShare {
+    pub share: f64,
+    /// What `claim_payout` would pay out in each mint with
+    /// the current shares, before fees
+    pub projected_payouts: Vec<MintAmount>,
+    pub claimed: bool,
+}
+
+/// Returns a project's `ProjectShare`. Nothing is
+/// written, so this is meant to be simulated.
+pub fn view_project_share(
+    ctx: Context<ViewProjectShare>,
+    _pool_id: u64,
+    _project_id: u64,
+) -> Result<ProjectShare> {
+    let pool = &ctx.accounts.pool;
+    let project_key = ctx.accounts.project.key();
+
+    let participant = pool.project_shares
+        .iter()
+        .find(|p| p.project_key == project_key)
+        .ok_or(ProtocolError::NotInPool)?;
+
+    Ok(ProjectShare {
+        share: participant.share_data.share,
+        projected_payouts: pool.calculate_payouts(&project_key)?,
+        claimed: participant.claimed,
+    })
+}
+
+#[derive(Accounts)]
+#[instruction(
+    pool_id: u64,
+    project_id: u64,
+)]
+pub struct ViewProjectShare<'info> {
+    #[account(
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        seeds = [
+            Project::SEED_PREFIX.as_bytes(),
+            project_id.to_le_bytes().as_ref(),
+        ],
+        bump = project.bump,
+    )]
+    pub project: Account<'info, Project>,
+}
@@ -1,40 +1,53 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token, associated_token};
+use anchor_spl::{associated_token, token_interface::{Mint, TokenAccount, TokenInterface}};
 
 use crate::error::ProtocolError;
 
 use crate::state::project::*;
+use crate::state::config::*;
+use crate::util::transfer_tokens;
 
-/// Withdraws all of the USDC from the vault
+/// Withdraws the project's full balance of a mint from the vault
 /// and sends to the beneficiary. Requires that the payer
 /// be a fundraiser admin.
 pub fn withdraw_all(ctx: Context<WithdrawAll>) -> Result<()> {
+    ctx.accounts.config.check(Feature::Withdrawals)?;
+
     let payer_key = ctx.accounts.payer.key();
     let project = &mut ctx.accounts.project;
 
     // Check to make sure caller is an admin
     require!(project.admins.contains(&payer_key), ProtocolError::NotAuthorized);
 
+    // Oracle accounts are passed in as remaining accounts. Campaigns
+    // are settled on their value the first time they're checked
+    // after the deadline
+    let current_time = Clock::get()?.unix_timestamp as u64;
+    project.settle_campaign(current_time, &ctx.accounts.config.oracle(), ctx.remaining_accounts)?;
+    project.refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts);
+    project.can_withdraw(current_time)?;
+
+    let mint_key = ctx.accounts.mint.key();
+    let amount = project.balance_of(&mint_key);
+
     let bump = project.bump.to_le_bytes();
     let id_ref = project.project_id.to_le_bytes();
 
     let seeds = vec![Project::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
     let signer_seeds = vec![seeds.as_slice()];
 
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.project_token_account.to_account_info(),
-                to: ctx.accounts.beneficiary_token_account.to_account_info(),
-                authority: project.to_account_info(),
-            },
-            &signer_seeds,
-        ),
-        project.balance.into(),
+    transfer_tokens(
+        ctx.accounts.project_token_account.to_account_info(),
+        ctx.accounts.beneficiary_token_account.to_account_info(),
+        project.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        amount,
     )?;
 
-    project.balance -= project.balance;
+    project.withdraw(&mint_key, amount)?;
+    project.refresh_value(&ctx.accounts.config.oracle(), ctx.remaining_accounts);
 
     Ok(())
 }
@@ -42,6 +55,7 @@ pub fn withdraw_all(ctx: Context<WithdrawAll>) -> Result<()> {
 #[derive(Accounts)]
 pub struct WithdrawAll<'info> {
     #[account( 
+        mut,
         seeds = [
             Project::SEED_PREFIX.as_bytes(),
             project.project_id.to_le_bytes().as_ref(),
@@ -57,17 +71,22 @@ pub struct WithdrawAll<'info> {
         token::mint = mint,
         token::authority = beneficiary,
     )]
-    pub beneficiary_token_account: Account<'info, token::TokenAccount>,
-    pub mint: Account<'info, token::Mint>,
+    pub beneficiary_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         token::mint = mint,
         token::authority = project,
     )]
-    pub project_token_account: Account<'info, token::TokenAccount>,
+    pub project_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, token::Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::{state::config::*, error::ProtocolError, util::transfer_tokens};
+
+/// Withdraws protocol fees from the treasury.
+/// Only the protocol authority can call this.
+pub fn withdraw_fees(
+    ctx: Context<WithdrawFees>,
+    amount: u64,
+) -> Result<()> {
+    let bump = ctx.accounts.config.treasury_bump.to_le_bytes();
+    let seeds = vec![ProtocolConfig::TREASURY_SEED_PREFIX.as_bytes(), &bump];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    transfer_tokens(
+        ctx.accounts.treasury_token_account.to_account_info(),
+        ctx.accounts.destination_token_account.to_account_info(),
+        ctx.accounts.treasury.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        amount,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+        has_one = authority @ ProtocolError::NotAuthorized,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    /// CHECK: Owns the treasury token accounts, holds no data
+    #[account(
+        seeds = [ProtocolConfig::TREASURY_SEED_PREFIX.as_bytes()],
+        bump = config.treasury_bump,
+    )]
+    pub treasury: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = treasury,
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,22 +1,56 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{token, associated_token};
+use anchor_spl::{associated_token, token_interface::{Mint, TokenAccount, TokenInterface}};
 
 use crate::error::ProtocolError;
 
-use crate::state::pool::*;
+use crate::state::{
+    config::*,
+    pool::*,
+    source::*,
+};
+use crate::util::transfer_tokens;
 
-/// Withdraws all of the USDC from the round
-/// and sends it back to the payer. Requires that the payer
-/// be a fundraiser admin. This can only be called before a round begins,
-/// or after a round is closed (if there is no participants).
+/// Refunds a `FundingSource` from the round in a mint, sending
+/// the funds back to the source's authority. Before a round begins, or if
+/// it's cancelled, the source's full deposit is returned. After a
+/// round ends, only the matching that can't be paid out to any
+/// participant is returned. Callable by a pool admin or the
+/// source's authority.
 pub fn withdraw_funds_from_round(ctx: Context<WithdrawFromRound>, pool_id: u64) -> Result<()> {
+    ctx.accounts.config.check(Feature::Funding)?;
+
     let payer_key = ctx.accounts.payer.key();
     let pool = &mut ctx.accounts.pool;
+    let source = &mut ctx.accounts.source;
+    let source_key = source.key();
+    let mint_key = ctx.accounts.mint.key();
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    // Check to make sure caller is an admin or the sponsor
+    require!(
+        pool.admins.contains(&payer_key) || source.authority == payer_key,
+        ProtocolError::NotAuthorized
+    );
+
+    let refund = match pool.state_at(current_time) {
+        PoolState::PendingStart | PoolState::Cancelled => {
+            let refund = pool.remove_source(&source_key, &mint_key);
+            source.withdraw(refund)?;
+            refund
+        },
+        PoolState::Ended | PoolState::Finalized | PoolState::Distributed => {
+            let refund = pool
+                .calculate_unspent_for_source(&source_key, &mint_key)
+                .saturating_sub(source.refunded_in(&mint_key));
+            source.record_refund(mint_key, refund)?;
+            refund
+        },
+        PoolState::Active => return Err(ProtocolError::PoolStillActive.into()),
+    };
 
-    // Check to make sure caller is an admin
-    require!(pool.admins.contains(&payer_key), ProtocolError::NotAuthorized);
+    require!(refund > 0, ProtocolError::NothingToRefund);
 
-    pool.can_withdraw()?;
+    msg!("Refunding {:?} to source {:?}", refund, source_key);
 
     let bump = pool.bump.to_le_bytes();
     let id_ref = pool_id.to_le_bytes();
@@ -24,17 +58,14 @@ pub fn withdraw_funds_from_round(ctx: Context<WithdrawFromRound>, pool_id: u64)
     let seeds = vec![Pool::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
     let signer_seeds = vec![seeds.as_slice()];
 
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            token::Transfer {
-                from: ctx.accounts.pool_token_account.to_account_info(),
-                to: ctx.accounts.payer_token_account.to_account_info(),
-                authority: pool.to_account_info(),
-            },
-            signer_seeds.as_slice(),
-        ),
-        pool.total_funding,
+    transfer_tokens(
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.authority_token_account.to_account_info(),
+        pool.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        refund,
     )?;
 
     Ok(())
@@ -45,7 +76,7 @@ pub fn withdraw_funds_from_round(ctx: Context<WithdrawFromRound>, pool_id: u64)
     _pool_id: u64
 )]
 pub struct WithdrawFromRound<'info> {
-    #[account( 
+    #[account(
         mut,
         seeds = [
             Pool::SEED_PREFIX.as_bytes(),
@@ -54,25 +85,40 @@ pub struct WithdrawFromRound<'info> {
         bump = pool.bump,
     )]
     pub pool: Account<'info, Pool>,
-    pub mint: Account<'info, token::Mint>,
     #[account(
         mut,
-        token::mint = mint,
-        token::authority = pool,
+        seeds = [
+            FundingSource::SEED_PREFIX.as_bytes(),
+            source.name.as_ref(),
+            source.pool_id.to_le_bytes().as_ref(),
+            source.amount.to_le_bytes().as_ref(),
+            source.authority.as_ref(),
+        ],
+        bump = source.bump,
+        constraint = source.pool_id == _pool_id @ ProtocolError::SourceMismatch,
     )]
-    pub pool_token_account: Account<'info, token::TokenAccount>,
+    pub source: Account<'info, FundingSource>,
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         token::mint = mint,
-        token::authority = payer,
+        token::authority = pool,
     )]
-    pub payer_token_account: Account<'info, token::TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
-        constraint = pool.admins.contains(&payer.key())
+        token::mint = mint,
+        token::authority = source.authority,
     )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, token::Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
 }
\ No newline at end of file
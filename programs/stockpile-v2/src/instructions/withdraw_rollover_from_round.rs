@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::ProtocolError;
+
+use crate::state::{
+    config::*,
+    pool::*,
+};
+use crate::util::{set_pool_and_maybe_realloc, transfer_tokens};
+
+/// Returns matching another pool rolled into the round, in a mint,
+/// back to the pool it came from. Before a round begins, or if it's
+/// cancelled, the full amount is returned. After a round ends, only
+/// the matching that can't be paid out to any participant is returned.
+/// The funding pool's funders can then withdraw it as unspent.
+/// Callable by an admin of either pool.
+pub fn withdraw_rollover_from_round(ctx: Context<WithdrawRolloverFromRound>, pool_id: u64) -> Result<()> {
+    ctx.accounts.config.check(Feature::Funding)?;
+
+    let payer_key = ctx.accounts.payer.key();
+    let pool = &mut ctx.accounts.pool;
+    let funder_key = ctx.accounts.funder_pool.key();
+    let mint_key = ctx.accounts.mint.key();
+    let current_time = Clock::get()?.unix_timestamp as u64;
+
+    require!(
+        pool.admins.contains(&payer_key) || ctx.accounts.funder_pool.admins.contains(&payer_key),
+        ProtocolError::NotAuthorized
+    );
+
+    let refund = match pool.state_at(current_time) {
+        PoolState::PendingStart | PoolState::Cancelled => pool.remove_source(&funder_key, &mint_key),
+        PoolState::Ended | PoolState::Finalized | PoolState::Distributed => {
+            pool.take_rolled_in_unspent(&funder_key, &mint_key)
+        },
+        PoolState::Active => return Err(ProtocolError::PoolStillActive.into()),
+    };
+
+    require!(refund > 0, ProtocolError::NothingToRefund);
+
+    msg!("Returning {:?} to pool {:?}", refund, funder_key);
+
+    let bump = pool.bump.to_le_bytes();
+    let id_ref = pool_id.to_le_bytes();
+
+    let seeds = vec![Pool::SEED_PREFIX.as_bytes(), id_ref.as_ref(), &bump];
+    let signer_seeds = vec![seeds.as_slice()];
+
+    let received = transfer_tokens(
+        ctx.accounts.pool_token_account.to_account_info(),
+        ctx.accounts.funder_pool_token_account.to_account_info(),
+        pool.to_account_info(),
+        &ctx.accounts.mint,
+        ctx.accounts.token_program.to_account_info(),
+        &signer_seeds,
+        refund,
+    )?;
+
+    // Recorded for the funding pool's funders, for
+    // what arrived after any transfer fee
+    let mut funder_pool = ctx.accounts.funder_pool.clone().into_inner();
+    funder_pool.record_returned(mint_key, received)?;
+    set_pool_and_maybe_realloc(
+        &mut ctx.accounts.funder_pool,
+        funder_pool,
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+    )?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(
+    _pool_id: u64
+)]
+pub struct WithdrawRolloverFromRound<'info> {
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            _pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = pool.bump,
+    )]
+    pub pool: Box<Account<'info, Pool>>,
+    /// The pool whose matching was rolled into `pool`
+    #[account(
+        mut,
+        seeds = [
+            Pool::SEED_PREFIX.as_bytes(),
+            funder_pool.pool_id.to_le_bytes().as_ref(),
+        ],
+        bump = funder_pool.bump,
+        constraint = funder_pool.pool_id != _pool_id @ ProtocolError::SourceMismatch,
+    )]
+    pub funder_pool: Box<Account<'info, Pool>>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = pool,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = funder_pool,
+    )]
+    pub funder_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        seeds = [ProtocolConfig::SEED_PREFIX.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
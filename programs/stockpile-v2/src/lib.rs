@@ -32,13 +32,19 @@ own risk. Please consult the license for more information.
 use anchor_lang::prelude::*;
 
 pub mod error;
+pub mod events;
 pub mod instructions;
+pub mod oracle;
 pub mod state;
 pub mod util;
 
 pub use instructions::*;
+use crate::state::config::*;
 use crate::state::pool::*;
 use crate::state::project::*;
+use crate::state::series::*;
+use crate::state::source::*;
+use crate::state::subscription::*;
 
 use solana_security_txt::security_txt;
 
@@ -58,27 +64,80 @@ declare_id!("STKUaKniasuqrfer3XNbmrrc578pkL1XACdK8H3YPu8");
 pub mod stockpile_v2 {
     use super::*;
 
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        gatekeeper_network: Pubkey,
+    ) -> Result<()> {
+        instructions::initialize_config(ctx, gatekeeper_network)
+    }
+
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        update: UpdateConfigField,
+    ) -> Result<()> {
+        instructions::update_config(ctx, update)
+    }
+
+    pub fn withdraw_fees(
+        ctx: Context<WithdrawFees>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_fees(ctx, amount)
+    }
+
+    pub fn prune_directory<'info>(
+        ctx: Context<'_, '_, '_, 'info, PruneDirectory<'info>>,
+        page: u32,
+    ) -> Result<()> {
+        instructions::prune_directory(ctx, page)
+    }
+
+    pub fn view_pool(
+        ctx: Context<ViewPool>,
+        pool_id: u64,
+    ) -> Result<PoolSummary> {
+        instructions::view_pool(ctx, pool_id)
+    }
+
+    pub fn view_project_share(
+        ctx: Context<ViewProjectShare>,
+        pool_id: u64,
+        project_id: u64,
+    ) -> Result<ProjectShare> {
+        instructions::view_project_share(ctx, pool_id, project_id)
+    }
+
+    pub fn view_marginal_match<'info>(
+        ctx: Context<'_, '_, '_, 'info, ViewMarginalMatch<'info>>,
+        pool_id: u64,
+        project_id: u64,
+        donor: Pubkey,
+        amount: u64,
+    ) -> Result<MarginalMatch> {
+        instructions::view_marginal_match(ctx, pool_id, project_id, donor, amount)
+    }
+
     pub fn create_project(
         ctx: Context<CreateProject>,
-        project_id: u64,
         name: String,
         admins: Vec<Pubkey>,
         beneficiary: Pubkey,
         goal: u64,
+        campaign: Option<Campaign>,
     ) -> Result<()> {
-        instructions::create_project(ctx, project_id, name, admins, beneficiary, goal)
+        instructions::create_project(ctx, name, admins, beneficiary, goal, campaign)
     }
 
     pub fn create_pool(
         ctx: Context<CreatePool>,
-        pool_id: u64,
         name: String,
         start: u64,
         end: u64,
         admins: Vec<Pubkey>,
-        access: PoolAccess
+        access: PoolAccess,
+        caps: MatchingCaps,
     ) -> Result<()> {
-        instructions::create_pool(ctx, pool_id, name, start, end, admins, access)
+        instructions::create_pool(ctx, name, start, end, admins, access, caps)
     }
 
     pub fn create_source(
@@ -86,8 +145,9 @@ pub mod stockpile_v2 {
         name: String,
         pool_id: u64,
         amount: u64,
+        earmark: Earmark,
     ) -> Result<()> {
-        instructions::create_source(ctx, name, pool_id, amount)
+        instructions::create_source(ctx, name, pool_id, amount, earmark)
     }
 
     pub fn create_milestone(
@@ -107,8 +167,8 @@ pub mod stockpile_v2 {
         instructions::contribute(ctx, _project_id, amount)
     }
 
-    pub fn contribute_with_vote(
-        ctx: Context<ContributeWithVote>,
+    pub fn contribute_with_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, ContributeWithVote<'info>>,
         _pool_id: u64,
         _project_id: u64,
         amount: u64,
@@ -116,6 +176,14 @@ pub mod stockpile_v2 {
         instructions::contribute_with_vote(ctx, _pool_id, _project_id, amount)
     }
 
+    pub fn contribute_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ContributeBatch<'info>>,
+        pool_id: u64,
+        contributions: Vec<BatchContribution>,
+    ) -> Result<()> {
+        instructions::contribute_batch(ctx, pool_id, contributions)
+    }
+
     pub fn deactivate_project(
         ctx: Context<DeactivateProject>,
     ) -> Result<()> {
@@ -126,16 +194,18 @@ pub mod stockpile_v2 {
         ctx: Context<AddProject>,
         _project_id: u64,
         _pool_id: u64,
+        category: Option<u8>,
     ) -> Result<()> {
-        instructions::add_project(ctx, _project_id, _pool_id)
+        instructions::add_project(ctx, _project_id, _pool_id, category)
     }
 
     pub fn join_pool(
         ctx: Context<JoinPool>,
         _project_id: u64,
         _pool_id: u64,
+        category: Option<u8>,
     ) -> Result<()> {
-        instructions::join_pool(ctx, _project_id, _pool_id)
+        instructions::join_pool(ctx, _project_id, _pool_id, category)
     }
 
     pub fn fund_pool(
@@ -159,8 +229,8 @@ pub mod stockpile_v2 {
         instructions::withdraw_all(ctx)
     }
 
-    pub fn close_project(
-        ctx: Context<CloseProject>
+    pub fn close_project<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseProject<'info>>
     ) -> Result<()> {
         instructions::close_project(ctx)
     }
@@ -171,6 +241,19 @@ pub mod stockpile_v2 {
         instructions::close_milestone(ctx)
     }
 
+    pub fn close_source(
+        ctx: Context<CloseSource>
+    ) -> Result<()> {
+        instructions::close_source(ctx)
+    }
+
+    pub fn close_pool<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClosePool<'info>>,
+        _pool_id: u64,
+    ) -> Result<()> {
+        instructions::close_pool(ctx, _pool_id)
+    }
+
     pub fn claim_payout(
         ctx: Context<ClaimPayout>,
         _project_id: u64,
@@ -179,6 +262,67 @@ pub mod stockpile_v2 {
         instructions::claim_payout(ctx, _project_id, _pool_id)
     }
 
+    pub fn distribute_payouts<'info>(
+        ctx: Context<'_, '_, '_, 'info, DistributePayouts<'info>>,
+        pool_id: u64,
+    ) -> Result<()> {
+        instructions::distribute_payouts(ctx, pool_id)
+    }
+
+    pub fn expire_claims<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExpireClaims<'info>>,
+        pool_id: u64,
+    ) -> Result<()> {
+        instructions::expire_claims(ctx, pool_id)
+    }
+
+    pub fn create_series(
+        ctx: Context<CreateSeries>,
+        name: String,
+        admins: Vec<Pubkey>,
+        template: SeriesTemplate,
+        first_start: u64,
+        max_rounds: Option<u32>,
+    ) -> Result<()> {
+        instructions::create_series(ctx, name, admins, template, first_start, max_rounds)
+    }
+
+    pub fn update_series(
+        ctx: Context<UpdateSeries>,
+        _series_id: u64,
+        update: UpdateSeriesField,
+    ) -> Result<()> {
+        instructions::update_series(ctx, _series_id, update)
+    }
+
+    pub fn spawn_round(
+        ctx: Context<SpawnRound>,
+        series_id: u64,
+    ) -> Result<()> {
+        instructions::spawn_round(ctx, series_id)
+    }
+
+    pub fn rollover_unspent<'info>(
+        ctx: Context<'_, '_, '_, 'info, RolloverUnspent<'info>>,
+        series_id: u64,
+    ) -> Result<()> {
+        instructions::rollover_unspent(ctx, series_id)
+    }
+
+    pub fn withdraw_rollover_from_round(
+        ctx: Context<WithdrawRolloverFromRound>,
+        _pool_id: u64
+    ) -> Result<()> {
+        instructions::withdraw_rollover_from_round(ctx, _pool_id)
+    }
+
+    pub fn carry_over_project(
+        ctx: Context<CarryOverProject>,
+        series_id: u64,
+    ) -> Result<()> {
+        instructions::carry_over_project(ctx, series_id)
+    }
+
     pub fn withdraw_funds_from_round(
         ctx: Context<WithdrawFromRound>,
         _pool_id: u64
@@ -209,4 +353,115 @@ pub mod stockpile_v2 {
     ) -> Result<()> {
         instructions::extend_pool_duration(ctx, _pool_id, new_end_date)
     }
+
+    pub fn reschedule_pool(
+        ctx: Context<ReschedulePool>,
+        _pool_id: u64,
+        new_start: u64,
+        new_end: u64,
+    ) -> Result<()> {
+        instructions::reschedule_pool(ctx, _pool_id, new_start, new_end)
+    }
+
+    pub fn close_pool_early(
+        ctx: Context<ClosePoolEarly>,
+        _pool_id: u64,
+    ) -> Result<()> {
+        instructions::close_pool_early(ctx, _pool_id)
+    }
+
+    pub fn finalize_pool(
+        ctx: Context<FinalizePool>,
+        _pool_id: u64,
+    ) -> Result<()> {
+        instructions::finalize_pool(ctx, _pool_id)
+    }
+
+    pub fn cancel_pool(
+        ctx: Context<CancelPool>,
+        _pool_id: u64,
+    ) -> Result<()> {
+        instructions::cancel_pool(ctx, _pool_id)
+    }
+
+    pub fn enable_vote_refunds(
+        ctx: Context<EnableVoteRefunds>,
+        _project_id: u64,
+        _pool_id: u64,
+    ) -> Result<()> {
+        instructions::enable_vote_refunds(ctx, _project_id, _pool_id)
+    }
+
+    pub fn refund_vote(
+        ctx: Context<RefundVote>,
+        _project_id: u64,
+        _pool_id: u64,
+    ) -> Result<()> {
+        instructions::refund_vote(ctx, _project_id, _pool_id)
+    }
+
+    pub fn create_subscription(
+        ctx: Context<CreateSubscription>,
+        _project_id: u64,
+        amount: u64,
+        period: u64,
+        allowance: u64,
+    ) -> Result<()> {
+        instructions::create_subscription(ctx, _project_id, amount, period, allowance)
+    }
+
+    pub fn execute_subscription(
+        ctx: Context<ExecuteSubscription>,
+    ) -> Result<()> {
+        instructions::execute_subscription(ctx)
+    }
+
+    pub fn update_subscription(
+        ctx: Context<UpdateSubscription>,
+        update: UpdateSubscriptionField,
+    ) -> Result<()> {
+        instructions::update_subscription(ctx, update)
+    }
+
+    pub fn cancel_subscription(
+        ctx: Context<CancelSubscription>,
+    ) -> Result<()> {
+        instructions::cancel_subscription(ctx)
+    }
+
+    pub fn refund_contribution(
+        ctx: Context<RefundContribution>,
+    ) -> Result<()> {
+        instructions::refund_contribution(ctx)
+    }
+
+    pub fn refresh_project_value(
+        ctx: Context<RefreshProjectValue>,
+    ) -> Result<()> {
+        instructions::refresh_project_value(ctx)
+    }
+
+    pub fn migrate_pool(
+        ctx: Context<MigratePool>,
+    ) -> Result<()> {
+        instructions::migrate_pool(ctx)
+    }
+
+    pub fn migrate_project(
+        ctx: Context<MigrateProject>,
+    ) -> Result<()> {
+        instructions::migrate_project(ctx)
+    }
+
+    pub fn migrate_milestone(
+        ctx: Context<MigrateMilestone>,
+    ) -> Result<()> {
+        instructions::migrate_milestone(ctx)
+    }
+
+    pub fn migrate_source(
+        ctx: Context<MigrateSource>,
+    ) -> Result<()> {
+        instructions::migrate_source(ctx)
+    }
 }
@@ -0,0 +1,358 @@
+use anchor_lang::prelude::*;
+use pyth_sdk_solana::{load_price_feed_from_account_info, Price};
+use std::collections::BTreeMap;
+use switchboard_solana::AggregatorAccountData;
+
+use crate::{
+    error::ProtocolError,
+    util::{
+        to_pubkey,
+        MAX_ORACLE_FEEDS,
+        MAX_ORACLE_SOURCES,
+        USDC_DECIMALS,
+        USDC_DEVNET_MINT,
+        USDC_MINT,
+        USDC_USD_PRICE_FEED_ID,
+    },
+};
+
+/// Per-pool settings for how oracle prices are accepted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct OracleConfig {
+    /// Oldest price accepted, in seconds
+    pub max_staleness: u64,
+    /// Widest confidence interval accepted, as a ratio of the price
+    pub max_confidence_ratio: f64,
+    /// Use the EMA price instead of the spot price
+    pub use_ema: bool,
+    /// Price sources for each mint. The USDC mints can go without
+    /// an entry, and use the default Pyth USDC/USD feed.
+    pub feeds: Vec<MintOracle>,
+}
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            max_staleness: 60,
+            max_confidence_ratio: 0.02,
+            use_ema: false,
+            feeds: vec![],
+        }
+    }
+}
+
+impl OracleConfig {
+    pub const SPACE: usize = 8 + 8 + 1 + 4 + (MintOracle::SPACE * MAX_ORACLE_FEEDS);
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_staleness > 0, ProtocolError::InvalidOracleConfig);
+        require!(
+            self.max_confidence_ratio > 0.0 && self.max_confidence_ratio < 1.0,
+            ProtocolError::InvalidOracleConfig
+        );
+        require!(self.feeds.len() <= MAX_ORACLE_FEEDS, ProtocolError::InvalidOracleConfig);
+        for feed in &self.feeds {
+            feed.validate()?;
+        }
+        Ok(())
+    }
+
+    /// The ordered list of sources to try for a mint
+    pub fn sources_for(&self, mint: &Pubkey) -> Vec<OracleSource> {
+        match self.feeds.iter().find(|f| f.mint == *mint) {
+            Some(feed) => feed.sources.clone(),
+            None if is_usdc(mint) => vec![OracleSource::Pyth(to_pubkey(USDC_USD_PRICE_FEED_ID))],
+            None => vec![],
+        }
+    }
+
+    /// The decimals a mint's base units are valued with
+    pub fn decimals_for(&self, mint: &Pubkey) -> Result<u8> {
+        match self.feeds.iter().find(|f| f.mint == *mint) {
+            Some(feed) => Ok(feed.decimals),
+            None if is_usdc(mint) => Ok(USDC_DECIMALS),
+            None => err!(ProtocolError::MintNotSupported),
+        }
+    }
+
+    /// Fails unless a mint can be priced, with the decimals it actually has
+    pub fn check_mint(&self, mint: &Pubkey, decimals: u8) -> Result<()> {
+        require!(
+            !self.sources_for(mint).is_empty() && self.decimals_for(mint)? == decimals,
+            ProtocolError::MintNotSupported
+        );
+        Ok(())
+    }
+
+    /// Converts an amount in a mint's base units to a USD value
+    pub fn value_usd(&self, mint: &Pubkey, amount: u64, prices: &BTreeMap<Pubkey, f64>) -> Result<f64> {
+        let price = prices
+            .get(mint)
+            .ok_or(ProtocolError::OraclePriceUnavailable)?;
+        Ok(calculate_price_usd(amount, self.decimals_for(mint)?, *price))
+    }
+
+    /// Loads a price for every mint, trying each of the mint's
+    /// sources in order until one returns a valid price
+    pub fn load_prices(&self, mints: &[Pubkey], accounts: &[AccountInfo<'_>]) -> Result<BTreeMap<Pubkey, f64>> {
+        let mut prices: BTreeMap<Pubkey, f64> = BTreeMap::new();
+
+        for mint in mints {
+            if prices.contains_key(mint) {
+                continue;
+            }
+            let price = load_price_with_fallback(&self.sources_for(mint), accounts, self)?;
+
+            msg!("Price for {:?}: {:?}", mint, price);
+
+            prices.insert(*mint, price);
+        }
+
+        Ok(prices)
+    }
+}
+
+/// The price sources for a mint, in order of preference
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct MintOracle {
+    pub mint: Pubkey,
+    /// The mint's decimals, which its prices are per whole token of
+    pub decimals: u8,
+    pub sources: Vec<OracleSource>,
+}
+
+impl MintOracle {
+    pub const SPACE: usize = 32 + 1 + 4 + (OracleSource::SPACE * MAX_ORACLE_SOURCES);
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.sources.len() > 0, ProtocolError::InvalidOracleConfig);
+        require!(self.sources.len() <= MAX_ORACLE_SOURCES, ProtocolError::InvalidOracleConfig);
+        for source in &self.sources {
+            if let OracleSource::FixedPeg(price) = source {
+                require!(*price > 0.0, ProtocolError::InvalidOracleConfig);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Debug)]
+pub enum OracleSource {
+    Pyth(Pubkey),
+    Switchboard(Pubkey),
+    /// A fixed USD price, for stablecoins. Needs no oracle account.
+    FixedPeg(f64),
+}
+
+impl OracleSource {
+    pub const SPACE: usize = 1 + 32;
+}
+
+/// A source of USD prices for one whole token
+pub trait Oracle {
+    fn price_usd(&self, config: &OracleConfig) -> Result<f64>;
+}
+
+pub struct PythOracle<'a, 'info> {
+    pub account: &'a AccountInfo<'info>,
+}
+
+impl Oracle for PythOracle<'_, '_> {
+    fn price_usd(&self, config: &OracleConfig) -> Result<f64> {
+        try_load_price(self.account, config)
+    }
+}
+
+pub struct SwitchboardOracle<'a, 'info> {
+    pub account: &'a AccountInfo<'info>,
+}
+
+impl Oracle for SwitchboardOracle<'_, '_> {
+    fn price_usd(&self, config: &OracleConfig) -> Result<f64> {
+        let data = self.account.try_borrow_data()?;
+        let feed = AggregatorAccountData::new_from_bytes(&data)?;
+
+        feed.check_staleness(Clock::get()?.unix_timestamp, config.max_staleness as i64)?;
+
+        let price: f64 = feed.get_result()?.try_into()?;
+        let std_deviation: f64 = feed.latest_confirmed_round.std_deviation.try_into()?;
+
+        check_confidence(price, std_deviation, config)?;
+
+        Ok(price)
+    }
+}
+
+pub struct FixedPegOracle {
+    pub price: f64,
+}
+
+impl Oracle for FixedPegOracle {
+    fn price_usd(&self, _config: &OracleConfig) -> Result<f64> {
+        Ok(self.price)
+    }
+}
+
+/// Tries each source in order, returning the first valid price.
+/// Every oracle account up to the price used has to be provided,
+/// so a caller can't pick the price by leaving accounts out.
+pub fn load_price_with_fallback(
+    sources: &[OracleSource],
+    accounts: &[AccountInfo<'_>],
+    config: &OracleConfig,
+) -> Result<f64> {
+    for source in sources {
+        let price = match source {
+            OracleSource::Pyth(key) => match accounts.iter().find(|a| a.key == key) {
+                Some(account) => PythOracle { account }.price_usd(config),
+                None => return Err(ProtocolError::OracleAccountMissing.into()),
+            },
+            OracleSource::Switchboard(key) => match accounts.iter().find(|a| a.key == key) {
+                Some(account) => SwitchboardOracle { account }.price_usd(config),
+                None => return Err(ProtocolError::OracleAccountMissing.into()),
+            },
+            OracleSource::FixedPeg(price) => FixedPegOracle { price: *price }.price_usd(config),
+        };
+
+        match price {
+            Ok(price) => return Ok(price),
+            Err(e) => msg!("Oracle {:?} failed, trying next source: {:?}", source, e),
+        }
+    }
+
+    Err(ProtocolError::OraclePriceUnavailable.into())
+}
+
+/// Loads a USD price from a Pyth account, scaled by the feed's exponent
+pub fn try_load_price(pyth_account: &AccountInfo<'_>, config: &OracleConfig) -> Result<f64> {
+    let price_feed = load_price_feed_from_account_info(pyth_account)
+        .map_err(|_| ProtocolError::PythPriceFeedLoadFailed)?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let price = if config.use_ema {
+        price_feed.get_ema_price_no_older_than(current_time, config.max_staleness)
+    } else {
+        price_feed.get_price_no_older_than(current_time, config.max_staleness)
+    };
+
+    match price {
+        Some(price) => scale_price(&price, config),
+        None => Err(ProtocolError::PythPriceFeedPriceFailed.into()),
+    }
+}
+
+/// Scales a Pyth price by its exponent, rejecting
+/// prices with too wide of a confidence interval
+pub fn scale_price(price: &Price, config: &OracleConfig) -> Result<f64> {
+    require!(price.price > 0, ProtocolError::PythPriceFeedPriceFailed);
+
+    let scale = 10_f64.powi(price.expo);
+    let scaled_price = price.price as f64 * scale;
+    let scaled_conf = price.conf as f64 * scale;
+
+    check_confidence(scaled_price, scaled_conf, config)?;
+
+    Ok(scaled_price)
+}
+
+fn check_confidence(price: f64, confidence: f64, config: &OracleConfig) -> Result<()> {
+    require!(price > 0.0, ProtocolError::PythPriceFeedPriceFailed);
+
+    if confidence / price > config.max_confidence_ratio {
+        msg!("Price confidence {:?} too wide for price {:?}", confidence, price);
+        return Err(ProtocolError::PythPriceConfidenceTooWide.into());
+    }
+
+    Ok(())
+}
+
+/// Converts an amount in a mint's base units to a USD value
+pub fn calculate_price_usd(amount: u64, decimals: u8, price: f64) -> f64 {
+    to_ui_amount(amount, decimals) * price
+}
+
+fn is_usdc(mint: &Pubkey) -> bool {
+    *mint == to_pubkey(USDC_MINT) || *mint == to_pubkey(USDC_DEVNET_MINT)
+}
+
+/// Converts base units to whole tokens
+pub fn to_ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10_f64.powi(decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(price: i64, conf: u64, expo: i32) -> Price {
+        Price {
+            price,
+            conf,
+            expo,
+            publish_time: 0,
+        }
+    }
+
+    #[test]
+    fn try_scale_price() -> Result<()> {
+        let config = OracleConfig::default();
+
+        // $0.99995 +/- $0.0001
+        let usdc = scale_price(&price(99_995_000, 10_000, -8), &config)?;
+        assert!((usdc - 0.99995).abs() < 1e-9);
+
+        // Confidence of 5% is rejected
+        assert!(scale_price(&price(100_000_000, 5_000_000, -8), &config).is_err());
+        assert!(scale_price(&price(-1, 0, -8), &config).is_err());
+
+        // 2.5 USDC at $0.99995
+        let value = calculate_price_usd(2_500_000, USDC_DECIMALS, usdc);
+        assert!((value - 2.4998750).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_fixed_peg_fallback() -> Result<()> {
+        let usdc = to_pubkey(USDC_MINT);
+        let pyth = Pubkey::new_unique();
+        let config = OracleConfig {
+            feeds: vec![MintOracle {
+                mint: usdc,
+                decimals: USDC_DECIMALS,
+                sources: vec![
+                    OracleSource::Pyth(pyth),
+                    OracleSource::FixedPeg(1.0),
+                ],
+            }],
+            ..Default::default()
+        };
+        config.validate()?;
+
+        // The oracle account has to be provided, even with a peg to fall back on
+        assert!(config.load_prices(&[usdc], &[]).is_err());
+
+        // The Pyth account can't be loaded, so the peg is used
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let account = AccountInfo::new(&pyth, false, false, &mut lamports, &mut data, &owner, false, 0);
+        let prices = config.load_prices(&[usdc, usdc], &[account])?;
+        assert_eq!(prices.get(&usdc), Some(&1.0));
+
+        // Default Pyth feed wasn't provided either
+        let devnet_usdc = to_pubkey(USDC_DEVNET_MINT);
+        assert!(config.load_prices(&[devnet_usdc], &[]).is_err());
+        assert_eq!(config.decimals_for(&devnet_usdc)?, USDC_DECIMALS);
+
+        // Other mints can only be priced with a feed of their own
+        let unknown = Pubkey::new_unique();
+        assert!(config.sources_for(&unknown).is_empty());
+        assert!(config.decimals_for(&unknown).is_err());
+        assert!(config.check_mint(&unknown, 6).is_err());
+        assert!(config.check_mint(&usdc, 9).is_err());
+        config.check_mint(&usdc, USDC_DECIMALS)?;
+
+        Ok(())
+    }
+}
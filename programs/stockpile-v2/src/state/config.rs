@@ -0,0 +1,632 @@
+use anchor_lang::prelude::*;
+
+use anchor_spl::token_interface::spl_token_2022::extension::ExtensionType;
+
+use crate::{
+    error::ProtocolError,
+    oracle::{MintOracle, OracleConfig, OracleSource},
+    util::{
+        restricted_extensions,
+        to_pubkey,
+        BPS_DENOMINATOR,
+        MAX_ADMIN_LEN,
+        MAX_FEE_BPS,
+        MAX_FEE_EXEMPT,
+        MAX_NAME_LEN,
+        MAX_ORACLE_FEEDS,
+        PAUSABLE_EXTENSION,
+        TRANSFER_HOOK_EXTENSION,
+        USDC_DECIMALS,
+        USDC_DEVNET_MINT,
+        USDC_MINT,
+        USDC_USD_PRICE_FEED_ID,
+    },
+};
+
+/// Protocol-wide settings, held in a single account
+/// controlled by the protocol authority.
+#[account]
+#[derive(Default)]
+pub struct ProtocolConfig {
+    pub version: u8,
+    pub authority: Pubkey,
+    /// Mints the protocol accepts, with the price sources new pools use for them
+    pub mints: Vec<MintOracle>,
+    /// Restricted Token-2022 extensions allowed for supported mints
+    pub extension_allowances: Vec<ExtensionAllowance>,
+    pub limits: ProtocolLimits,
+    /// Stops every instruction except config updates
+    pub paused: bool,
+    /// Bitmask of paused `Feature`s
+    pub paused_features: u32,
+    pub fees: FeeConfig,
+    /// Civic gatekeeper network voters need a pass from
+    pub gatekeeper_network: Pubkey,
+    /// Id the next `Project` is created with
+    pub next_project_id: u64,
+    /// Id the next `Pool` is created with
+    pub next_pool_id: u64,
+    /// Id the next `RoundSeries` is created with
+    pub next_series_id: u64,
+    /// Page of the `PoolDirectory` new pools are added to
+    pub directory_page: u32,
+    /// Bump for the treasury PDA, which owns the token
+    /// accounts protocol fees are paid into
+    pub treasury_bump: u8,
+    pub bump: u8,
+}
+
+impl ProtocolConfig {
+    pub const SEED_PREFIX: &'static str = "config";
+
+    pub const TREASURY_SEED_PREFIX: &'static str = "treasury";
+
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 32                        // Pubkey
+        + 4 + (MintOracle::SPACE * MAX_ORACLE_FEEDS) // Vec<MintOracle>
+        + 4 + (ExtensionAllowance::SPACE * MAX_ORACLE_FEEDS) // Vec<ExtensionAllowance>
+        + ProtocolLimits::SPACE     // ProtocolLimits
+        + 1                         // bool
+        + 4                         // u32
+        + FeeConfig::SPACE          // FeeConfig
+        + 32                        // Pubkey
+        + 8                         // u64
+        + 8                         // u64
+        + 8                         // u64
+        + 4                         // u32
+        + 1                         // u8
+        + 1;                        // u8
+
+    pub fn new(authority: Pubkey, treasury_bump: u8, bump: u8) -> Self {
+        let usdc_usd = vec![OracleSource::Pyth(to_pubkey(USDC_USD_PRICE_FEED_ID))];
+        Self {
+            version: Self::VERSION,
+            authority,
+            mints: vec![
+                MintOracle { mint: to_pubkey(USDC_MINT), decimals: USDC_DECIMALS, sources: usdc_usd.clone() },
+                MintOracle { mint: to_pubkey(USDC_DEVNET_MINT), decimals: USDC_DECIMALS, sources: usdc_usd },
+            ],
+            extension_allowances: vec![],
+            limits: ProtocolLimits::default(),
+            paused: false,
+            paused_features: 0,
+            fees: FeeConfig::default(),
+            gatekeeper_network: Pubkey::default(),
+            next_project_id: 1,
+            next_pool_id: 1,
+            next_series_id: 1,
+            directory_page: 0,
+            treasury_bump,
+            bump,
+        }
+    }
+
+    /// Fails if the protocol, or the given feature, is paused
+    pub fn check(&self, feature: Feature) -> Result<()> {
+        require!(!self.paused, ProtocolError::ProtocolPaused);
+        require!(self.paused_features & feature.bit() == 0, ProtocolError::FeaturePaused);
+        Ok(())
+    }
+
+    pub fn set_feature_paused(&mut self, feature: Feature, paused: bool) {
+        if paused {
+            self.paused_features |= feature.bit();
+        } else {
+            self.paused_features &= !feature.bit();
+        }
+    }
+
+    /// Allocates the next project id
+    pub fn take_project_id(&mut self) -> Result<u64> {
+        let id = self.next_project_id;
+        self.next_project_id = id.checked_add(1).ok_or(ProtocolError::InvalidIdCounter)?;
+        Ok(id)
+    }
+
+    /// Allocates the next pool id
+    pub fn take_pool_id(&mut self) -> Result<u64> {
+        let id = self.next_pool_id;
+        self.next_pool_id = id.checked_add(1).ok_or(ProtocolError::InvalidIdCounter)?;
+        Ok(id)
+    }
+
+    /// Allocates the next series id
+    pub fn take_series_id(&mut self) -> Result<u64> {
+        let id = self.next_series_id;
+        self.next_series_id = id.checked_add(1).ok_or(ProtocolError::InvalidIdCounter)?;
+        Ok(id)
+    }
+
+    /// Moves the counters past ids that are already taken, e.g. by
+    /// accounts created before ids were allocated by the protocol.
+    /// Counters can only move forward, so ids are never reused.
+    pub fn skip_ids(&mut self, next_project_id: u64, next_pool_id: u64) -> Result<()> {
+        require!(
+            next_project_id >= self.next_project_id && next_pool_id >= self.next_pool_id,
+            ProtocolError::InvalidIdCounter
+        );
+        self.next_project_id = next_project_id;
+        self.next_pool_id = next_pool_id;
+        Ok(())
+    }
+
+    pub fn mint_is_supported(&self, mint: &Pubkey) -> Result<()> {
+        require!(
+            self.mints.iter().any(|m| m.mint == *mint),
+            ProtocolError::MintNotSupported
+        );
+        Ok(())
+    }
+
+    /// Fails if the mint isn't supported, or has restricted
+    /// extensions that haven't been allowed for it
+    pub fn check_mint(&self, mint: &AccountInfo) -> Result<()> {
+        self.mint_is_supported(mint.key)?;
+        let found = restricted_extensions(&mint.try_borrow_data()?)?;
+        require!(
+            found & !self.allowed_extensions(mint.key) == 0,
+            ProtocolError::MintExtensionNotAllowed
+        );
+        Ok(())
+    }
+
+    pub fn allowed_extensions(&self, mint: &Pubkey) -> u32 {
+        self.extension_allowances
+            .iter()
+            .find(|a| a.mint == *mint)
+            .map_or(0, |a| a.extensions)
+    }
+
+    /// Replaces the restricted extensions allowed for a supported
+    /// mint. Passing none removes the mint's allowance.
+    pub fn allow_extensions(&mut self, mint: Pubkey, extensions: &[RestrictedExtension]) -> Result<()> {
+        self.mint_is_supported(&mint)?;
+        self.extension_allowances.retain(|a| a.mint != mint);
+        if !extensions.is_empty() {
+            self.extension_allowances.push(ExtensionAllowance {
+                mint,
+                extensions: extensions.iter().fold(0, |bits, e| bits | e.bit()),
+            });
+        }
+        Ok(())
+    }
+
+    /// Prices the supported mints with their protocol feeds,
+    /// for valuing what projects hold
+    pub fn oracle(&self) -> OracleConfig {
+        OracleConfig {
+            max_staleness: self.limits.max_staleness,
+            feeds: self.mints.clone(),
+            ..Default::default()
+        }
+    }
+
+    pub fn remove_mint(&mut self, mint: &Pubkey) {
+        self.mints.retain(|m| m.mint != *mint);
+        self.extension_allowances.retain(|a| a.mint != *mint);
+    }
+
+    pub fn add_mint(&mut self, mint: MintOracle) -> Result<()> {
+        mint.validate()?;
+        self.mints.retain(|m| m.mint != mint.mint);
+        require!(self.mints.len() < MAX_ORACLE_FEEDS, ProtocolError::InvalidOracleConfig);
+        self.mints.push(mint);
+        Ok(())
+    }
+
+    pub fn validate_name(&self, name: &str) -> Result<()> {
+        require!(
+            name.as_bytes().len() <= self.limits.max_name_len as usize,
+            ProtocolError::NameTooLong
+        );
+        Ok(())
+    }
+
+    pub fn treasury(&self) -> Result<Pubkey> {
+        Pubkey::create_program_address(
+            &[Self::TREASURY_SEED_PREFIX.as_bytes(), &[self.treasury_bump]],
+            &crate::ID,
+        ).map_err(|_| ProtocolError::InvalidTreasury.into())
+    }
+
+    /// Splits the fees off an amount. Nothing is taken if any of
+    /// the parties is exempt, and the frontend fee is only taken
+    /// when a frontend account was passed in.
+    pub fn calculate_fees(
+        &self,
+        kind: FeeKind,
+        amount: u64,
+        parties: &[Pubkey],
+        has_frontend: bool,
+    ) -> Result<Fees> {
+        if parties.iter().any(|p| self.fees.exempt.contains(p)) {
+            return Ok(Fees::default());
+        }
+
+        let protocol_bps = match kind {
+            FeeKind::Contribution => self.fees.contribution_bps,
+            FeeKind::Payout => self.fees.payout_bps,
+        };
+        let frontend_bps = if has_frontend { self.fees.frontend_bps } else { 0 };
+
+        Ok(Fees {
+            protocol: bps_of(amount, protocol_bps)?,
+            frontend: bps_of(amount, frontend_bps)?,
+        })
+    }
+
+    pub fn validate_admins(&self, admins: &[Pubkey]) -> Result<()> {
+        require!(
+            admins.len() <= self.limits.max_admins as usize,
+            ProtocolError::TooManyAdmins
+        );
+        Ok(())
+    }
+}
+
+/// Limits applied across the protocol. They can be tightened
+/// below, but never exceed, the limits accounts are sized for.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct ProtocolLimits {
+    pub max_name_len: u32,
+    pub max_admins: u8,
+    /// Default for the oldest price new pools accept, in seconds
+    pub max_staleness: u64,
+}
+impl Default for ProtocolLimits {
+    fn default() -> Self {
+        Self {
+            max_name_len: MAX_NAME_LEN as u32,
+            max_admins: MAX_ADMIN_LEN as u8,
+            max_staleness: 60,
+        }
+    }
+}
+
+impl ProtocolLimits {
+    pub const SPACE: usize = 4 + 1 + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_name_len as usize <= MAX_NAME_LEN, ProtocolError::InvalidProtocolLimits);
+        require!(self.max_admins > 0, ProtocolError::InvalidProtocolLimits);
+        require!(self.max_admins as usize <= MAX_ADMIN_LEN, ProtocolError::InvalidProtocolLimits);
+        require!(self.max_staleness > 0, ProtocolError::InvalidProtocolLimits);
+        Ok(())
+    }
+}
+
+/// Fees, in basis points, on the amounts moving
+/// through the protocol
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq)]
+pub struct FeeConfig {
+    /// Protocol fee on direct contributions and votes
+    pub contribution_bps: u16,
+    /// Protocol fee on matching payouts
+    pub payout_bps: u16,
+    /// Fee paid to the frontend that routed the transaction
+    pub frontend_bps: u16,
+    /// Donors, projects and claimants that pay no fees
+    pub exempt: Vec<Pubkey>,
+}
+
+impl FeeConfig {
+    pub const SPACE: usize = 2 + 2 + 2 + 4 + (32 * MAX_FEE_EXEMPT);
+
+    pub fn validate(&self) -> Result<()> {
+        let protocol_bps = self.contribution_bps.max(self.payout_bps);
+        require!(
+            protocol_bps.saturating_add(self.frontend_bps) <= MAX_FEE_BPS,
+            ProtocolError::FeeTooHigh
+        );
+        require!(self.exempt.len() <= MAX_FEE_EXEMPT, ProtocolError::TooManyFeeExemptions);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeeKind {
+    Contribution,
+    Payout,
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub struct Fees {
+    pub protocol: u64,
+    pub frontend: u64,
+}
+
+impl Fees {
+    pub fn total(&self) -> u64 {
+        self.protocol + self.frontend
+    }
+}
+
+fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    let fee = (amount as u128) * (bps as u128) / (BPS_DENOMINATOR as u128);
+    u64::try_from(fee).map_err(|_| ProtocolError::AlgorithmFailure.into())
+}
+
+/// Groups of instructions that can be paused independently
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Feature {
+    Projects,
+    Pools,
+    Funding,
+    Contributions,
+    Withdrawals,
+    Refunds,
+    Subscriptions,
+    Migrations,
+}
+
+impl Feature {
+    pub fn bit(&self) -> u32 {
+        1 << (*self as u32)
+    }
+}
+
+/// Token-2022 mint extensions that let someone other than the holder
+/// move, lock or hide tokens, or that stop them moving at all. Mints
+/// with them are rejected unless the extension is allowed for the mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum RestrictedExtension {
+    PermanentDelegate,
+    NonTransferable,
+    MintCloseAuthority,
+    DefaultAccountState,
+    ConfidentialTransfer,
+    TransferHook,
+    Pausable,
+}
+
+impl RestrictedExtension {
+    pub fn bit(&self) -> u32 {
+        1 << (*self as u32)
+    }
+
+    /// Maps a raw Token-2022 extension type, including
+    /// ones newer than the token library, to its restriction
+    pub fn from_extension_type(extension: u16) -> Option<Self> {
+        match extension {
+            TRANSFER_HOOK_EXTENSION => return Some(Self::TransferHook),
+            PAUSABLE_EXTENSION => return Some(Self::Pausable),
+            _ => {},
+        }
+        match ExtensionType::try_from(extension).ok()? {
+            ExtensionType::PermanentDelegate => Some(Self::PermanentDelegate),
+            ExtensionType::NonTransferable => Some(Self::NonTransferable),
+            ExtensionType::MintCloseAuthority => Some(Self::MintCloseAuthority),
+            ExtensionType::DefaultAccountState => Some(Self::DefaultAccountState),
+            ExtensionType::ConfidentialTransferMint => Some(Self::ConfidentialTransfer),
+            _ => None,
+        }
+    }
+}
+
+/// Restricted extensions, as a bitmask of `RestrictedExtension`s,
+/// the protocol authority has allowed for a mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct ExtensionAllowance {
+    pub mint: Pubkey,
+    pub extensions: u32,
+}
+
+impl ExtensionAllowance {
+    pub const SPACE: usize = 32 + 4;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum UpdateConfigField {
+    Authority(Pubkey),
+    AddMint(MintOracle),
+    RemoveMint(Pubkey),
+    Limits(ProtocolLimits),
+    Pause(bool),
+    PauseFeature(Feature, bool),
+    Fees(FeeConfig),
+    /// Next project and pool ids
+    SkipIds(u64, u64),
+    AllowExtensions(Pubkey, Vec<RestrictedExtension>),
+    GatekeeperNetwork(Pubkey),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{pool::*, source::Earmark};
+    use crate::util::transfer_fee_on;
+    use std::collections::BTreeMap;
+    use anchor_spl::token_interface::spl_token_2022::{
+        extension::{
+            permanent_delegate::PermanentDelegate,
+            transfer_fee::TransferFeeConfig,
+            StateWithExtensionsMut,
+        },
+        state::Mint,
+    };
+
+    #[test]
+    fn try_pause_switches() -> Result<()> {
+        let mut config = ProtocolConfig::new(Pubkey::new_unique(), 255, 255);
+        assert!(config.check(Feature::Contributions).is_ok());
+
+        config.set_feature_paused(Feature::Contributions, true);
+        assert!(config.check(Feature::Contributions).is_err());
+        assert!(config.check(Feature::Withdrawals).is_ok());
+
+        config.paused = true;
+        assert!(config.check(Feature::Withdrawals).is_err());
+
+        config.paused = false;
+        config.set_feature_paused(Feature::Contributions, false);
+        assert!(config.check(Feature::Contributions).is_ok());
+
+        config.mint_is_supported(&to_pubkey(USDC_MINT))?;
+        assert!(config.mint_is_supported(&Pubkey::new_unique()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_allocate_ids() -> Result<()> {
+        let mut config = ProtocolConfig::new(Pubkey::new_unique(), 255, 255);
+        assert_eq!(config.take_project_id()?, 1);
+        assert_eq!(config.take_project_id()?, 2);
+        assert_eq!(config.take_pool_id()?, 1);
+
+        // Skip past ids squatted before the counter existed
+        config.skip_ids(1_000, 50)?;
+        assert_eq!(config.take_project_id()?, 1_000);
+        assert_eq!(config.take_pool_id()?, 50);
+
+        // Never backwards
+        assert!(config.skip_ids(10, 51).is_err());
+        assert!(config.skip_ids(1_001, 10).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_calculate_fees() -> Result<()> {
+        let mut config = ProtocolConfig::new(Pubkey::new_unique(), 255, 255);
+        let donor = Pubkey::new_unique();
+
+        // No fees by default
+        assert_eq!(config.calculate_fees(FeeKind::Contribution, 1_000_000, &[donor], true)?, Fees::default());
+
+        config.fees = FeeConfig {
+            contribution_bps: 100,
+            payout_bps: 250,
+            frontend_bps: 50,
+            exempt: vec![],
+        };
+        config.fees.validate()?;
+
+        let fees = config.calculate_fees(FeeKind::Contribution, 1_000_000, &[donor], true)?;
+        assert_eq!(fees, Fees { protocol: 10_000, frontend: 5_000 });
+        assert_eq!(fees.total(), 15_000);
+
+        // The frontend is only paid when it routed the transaction
+        let fees = config.calculate_fees(FeeKind::Payout, 1_000_000, &[donor], false)?;
+        assert_eq!(fees, Fees { protocol: 25_000, frontend: 0 });
+
+        // Rounds down
+        let fees = config.calculate_fees(FeeKind::Contribution, 99, &[donor], true)?;
+        assert_eq!(fees, Fees::default());
+
+        config.fees.exempt.push(donor);
+        let fees = config.calculate_fees(FeeKind::Payout, 1_000_000, &[Pubkey::new_unique(), donor], true)?;
+        assert_eq!(fees, Fees::default());
+
+        // Capped
+        config.fees.payout_bps = MAX_FEE_BPS;
+        assert!(config.fees.validate().is_err());
+
+        Ok(())
+    }
+
+    fn mint_data(extensions: &[ExtensionType]) -> Vec<u8> {
+        let mut data = vec![0; ExtensionType::get_account_len::<Mint>(extensions)];
+        let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        for extension in extensions {
+            match extension {
+                ExtensionType::PermanentDelegate => {
+                    mint.init_extension::<PermanentDelegate>(true).unwrap();
+                },
+                ExtensionType::TransferFeeConfig => {
+                    let config = mint.init_extension::<TransferFeeConfig>(true).unwrap();
+                    config.newer_transfer_fee.transfer_fee_basis_points = 100.into();
+                    config.newer_transfer_fee.maximum_fee = 5_000.into();
+                },
+                _ => unreachable!(),
+            }
+        }
+        mint.base = Mint { decimals: 6, is_initialized: true, ..Default::default() };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn try_mint_extensions() -> Result<()> {
+        let mut config = ProtocolConfig::new(Pubkey::new_unique(), 255, 255);
+        let usdc = to_pubkey(USDC_MINT);
+        let owner = anchor_spl::token_interface::ID;
+        let mut lamports = 0;
+
+        // Transfer fees are allowed, and charged on what arrives
+        let mut data = mint_data(&[ExtensionType::TransferFeeConfig]);
+        assert_eq!(transfer_fee_on(&data, 100_000, 0)?, 1_000);
+        assert_eq!(transfer_fee_on(&data, 10_000_000, 0)?, 5_000);
+        let mint = AccountInfo::new(&usdc, false, false, &mut lamports, &mut data, &owner, false, 0);
+        config.check_mint(&mint)?;
+
+        // A permanent delegate has to be allowed for the mint
+        let mut lamports = 0;
+        let mut data = mint_data(&[ExtensionType::PermanentDelegate]);
+        assert_eq!(transfer_fee_on(&data, 100_000, 0)?, 0);
+        let mint = AccountInfo::new(&usdc, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert!(config.check_mint(&mint).is_err());
+        config.allow_extensions(usdc, &[RestrictedExtension::PermanentDelegate])?;
+        config.check_mint(&mint)?;
+
+        // Extensions newer than the token library are still read,
+        // so a transfer hook or pause authority has to be allowed
+        for (extension_type, restricted) in [
+            (TRANSFER_HOOK_EXTENSION, RestrictedExtension::TransferHook),
+            (PAUSABLE_EXTENSION, RestrictedExtension::Pausable),
+        ] {
+            let mut lamports = 0;
+            let mut data = mint_data(&[ExtensionType::TransferFeeConfig]);
+            data.extend(extension_type.to_le_bytes());
+            data.extend(32u16.to_le_bytes());
+            data.extend([0; 32]);
+            assert_eq!(transfer_fee_on(&data, 100_000, 0)?, 1_000);
+            let mint = AccountInfo::new(&usdc, false, false, &mut lamports, &mut data, &owner, false, 0);
+            assert!(config.check_mint(&mint).is_err());
+            config.allow_extensions(usdc, &[RestrictedExtension::PermanentDelegate, restricted])?;
+            config.check_mint(&mint)?;
+        }
+
+        config.remove_mint(&usdc);
+        assert_eq!(config.allowed_extensions(&usdc), 0);
+        assert!(config.allow_extensions(usdc, &[RestrictedExtension::NonTransferable]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_price_added_mint() -> Result<()> {
+        let mut config = ProtocolConfig::new(Pubkey::new_unique(), 255, 255);
+        let mint = Pubkey::new_unique();
+        let project = Pubkey::new_unique();
+
+        // Mints added to the config are priced by the feeds the pools copy
+        assert!(config.oracle().check_mint(&mint, 9).is_err());
+        config.add_mint(MintOracle {
+            mint,
+            decimals: 9,
+            sources: vec![OracleSource::FixedPeg(2.0)],
+        })?;
+        let oracle = config.oracle();
+        assert_eq!(oracle.max_staleness, config.limits.max_staleness);
+        oracle.check_mint(&mint, 9)?;
+        assert!(oracle.check_mint(&mint, 6).is_err());
+        assert_eq!(oracle.value_usd(&mint, 1_500_000_000, &BTreeMap::from([(mint, 2.0)]))?, 3.0);
+
+        let mut pool = Pool {
+            project_shares: vec![Participant::new(project, PoolShare::new(), None)],
+            funders: vec![FundingTicket::new(Pubkey::new_unique(), Some(mint), 1_000, Earmark::Unrestricted)],
+            oracle,
+            ..Default::default()
+        };
+        pool.record_vote(&project, Pubkey::new_unique(), mint, 1_000_000_000)?;
+        pool.update_shares(&[])?;
+        assert_eq!(pool.project_shares[0].share_data.share, 1.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+/// Looks up the projects and pools an account has created.
+/// Ids are appended as they're allocated, and aren't removed
+/// when the project or pool is later closed.
+#[account]
+#[derive(Default)]
+pub struct CreatorIndex {
+    pub version: u8,
+    pub creator: Pubkey,
+    pub projects: Vec<u64>,
+    pub pools: Vec<u64>,
+    pub bump: u8,
+}
+
+impl CreatorIndex {
+    pub const SEED_PREFIX: &'static str = "creator";
+
+    pub const VERSION: u8 = 1;
+
+    /// Initial size, the account grows as ids are added
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 32                        // Pubkey
+        + 4 + (8 * 4)               // Vec<u64>
+        + 4 + (8 * 4)               // Vec<u64>
+        + 1;                        // u8
+
+    pub fn new(creator: Pubkey, bump: u8) -> Self {
+        Self {
+            version: Self::VERSION,
+            creator,
+            projects: vec![],
+            pools: vec![],
+            bump,
+        }
+    }
+}
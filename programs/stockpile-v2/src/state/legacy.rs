@@ -0,0 +1,559 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::error::ProtocolError;
+use crate::oracle::OracleConfig;
+use crate::state::{
+    metadata::Metadata,
+    milestone::*,
+    pool::*,
+    project::*,
+    source::*,
+};
+
+/// An account that can be rewritten from the layout
+/// it had before accounts carried a version byte.
+/// Legacy accounts didn't record who paid their rent, so
+/// it's returned to whichever admin closes them.
+pub trait Migrate: Sized {
+    const VERSION: u8;
+
+    type Legacy: AnchorDeserialize;
+
+    fn version(&self) -> u8;
+
+    fn from_legacy(legacy: Self::Legacy) -> Self;
+}
+
+/// Reads an account's data in its current or legacy layout, returning
+/// the account in the current layout. Accounts already on the current
+/// version can't be migrated again.
+pub fn migrate_account_data<T>(data: &[u8]) -> Result<T>
+where
+    T: Migrate + AccountDeserialize + Discriminator,
+{
+    require!(
+        data.len() >= 8 && data[..8] == T::DISCRIMINATOR,
+        ProtocolError::AccountNotMigratable
+    );
+
+    if let Ok(current) = T::try_deserialize(&mut &data[..]) {
+        if current.version() == T::VERSION {
+            return Err(ProtocolError::AccountAlreadyMigrated.into());
+        }
+    }
+
+    let legacy = T::Legacy::deserialize(&mut &data[8..])
+        .map_err(|_| ProtocolError::AccountNotMigratable)?;
+
+    Ok(T::from_legacy(legacy))
+}
+
+/// Writes an account in its current layout, growing it to at
+/// least `min_space` and funding the extra rent from the payer.
+/// Anyone can migrate an account, since its data is carried over
+/// as is, unless the instruction needs to be told something more.
+pub fn write_migrated_account<'info, T>(
+    account_info: &AccountInfo<'info>,
+    account: &T,
+    min_space: usize,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()>
+where
+    T: AccountSerialize + AnchorSerialize,
+{
+    let new_size = (8 + account.try_to_vec()?.len()).max(min_space);
+    crate::util::maybe_realloc(account_info, new_size, payer, system_program)?;
+
+    let mut data = account_info.try_borrow_mut_data()?;
+    data.fill(0);
+    let mut writer: &mut [u8] = &mut data;
+    account.try_serialize(&mut writer)?;
+
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PoolV0 {
+    pub pool_id: u64,
+    pub name: String,
+    pub total_funding: u64,
+    pub balance: u64,
+    pub start: u64,
+    pub end: u64,
+    pub admins: Vec<Pubkey>,
+    pub project_shares: Vec<ParticipantV0>,
+    pub funders: Vec<FundingTicketV0>,
+    pub pool_state: PoolStateV0,
+    pub pool_access: PoolAccess,
+    pub bump: u8,
+}
+
+/// Legacy pools were `Closed` by an admin while they were still
+/// running, which is now a cancellation, unless payouts were claimed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum PoolStateV0 {
+    PendingStart,
+    Active,
+    Distributed,
+    Closed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ParticipantV0 {
+    pub project_key: Pubkey,
+    pub claimed: bool,
+    pub share: f64,
+    pub votes: Vec<VoteTicketV0>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VoteTicketV0 {
+    pub payer: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FundingTicketV0 {
+    pub source: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+impl Migrate for Pool {
+    const VERSION: u8 = Pool::VERSION;
+
+    type Legacy = PoolV0;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn from_legacy(legacy: PoolV0) -> Self {
+        let any_claimed = legacy.project_shares.iter().any(|p| p.claimed);
+        let pool_state = match legacy.pool_state {
+            PoolStateV0::PendingStart => PoolState::PendingStart,
+            PoolStateV0::Active => PoolState::Active,
+            PoolStateV0::Distributed => PoolState::Distributed,
+            PoolStateV0::Closed if any_claimed => PoolState::Distributed,
+            PoolStateV0::Closed => PoolState::Cancelled,
+        };
+        let project_shares = legacy.project_shares
+            .into_iter()
+            .map(|p| {
+                let votes = p.votes
+                    .into_iter()
+                    .map(|v| VoteTicket::new(v.payer, v.mint, v.amount))
+                    .collect();
+                Participant {
+                    project_key: p.project_key,
+                    claimed: p.claimed,
+                    share_data: PoolShare { share: p.share, votes },
+                    category: None,
+                    refund_votes: false,
+                    paid: vec![],
+                    forfeited: false,
+                }
+            })
+            .collect();
+        let funders = legacy.funders
+            .into_iter()
+            .map(|t| FundingTicket::new(t.source, t.mint, t.amount, Earmark::Unrestricted))
+            .collect();
+
+        let mut pool = Self {
+            version: Pool::VERSION,
+            pool_id: legacy.pool_id,
+            name: legacy.name,
+            total_funding: legacy.total_funding,
+            balance: legacy.balance,
+            start: legacy.start,
+            end: legacy.end,
+            admins: legacy.admins,
+            project_shares,
+            funders,
+            pool_state,
+            pool_access: legacy.pool_access,
+            bump: legacy.bump,
+            caps: MatchingCaps::default(),
+            categories: vec![],
+            oracle: OracleConfig::default(),
+            history: vec![],
+            soft_close: None,
+            extended_by: 0,
+            rent_payers: vec![],
+            metadata: Metadata::default(),
+            unclaimed: None,
+            returned: vec![],
+        };
+
+        mark_legacy_claims(&mut pool);
+
+        pool
+    }
+}
+
+/// Legacy funding tickets were all recorded against the funder's wallet
+/// and the devnet USDC mint, whatever was actually deposited. Each is
+/// rewritten to the wallet's `FundingSource` for the pool, and to the
+/// mint the pool's vault holds, so it can be paid out and refunded.
+pub fn rebind_legacy_funders(pool: &mut Pool, mint: Pubkey, sources: &[(Pubkey, FundingSource)]) -> Result<()> {
+    let pool_id = pool.pool_id;
+    for ticket in pool.funders.iter_mut() {
+        let (source_key, _) = sources
+            .iter()
+            .find(|(_, s)| s.authority == ticket.source && s.pool_id == pool_id)
+            .ok_or(ProtocolError::SourceMismatch)?;
+        ticket.source = *source_key;
+        ticket.mint = Some(mint);
+    }
+
+    mark_legacy_claims(pool);
+
+    Ok(())
+}
+
+/// Legacy claims paid out the whole payout, so
+/// claimed participants have nothing left to claim
+fn mark_legacy_claims(pool: &mut Pool) {
+    let paid: Vec<Vec<MintAmount>> = pool.project_shares
+        .iter()
+        .map(|p| match p.claimed {
+            true => pool.calculate_payouts(&p.project_key).unwrap_or_default(),
+            false => vec![],
+        })
+        .collect();
+    for (participant, paid) in pool.project_shares.iter_mut().zip(paid) {
+        participant.paid = paid;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ProjectV0 {
+    pub project_id: u64,
+    pub name: String,
+    pub raised: u64,
+    pub goal: u64,
+    pub balance: u64,
+    pub contributors: u8,
+    pub admins: Vec<Pubkey>,
+    pub beneficiary: Pubkey,
+    pub bump: u8,
+    pub status: ProjectStatus,
+}
+
+impl Migrate for Project {
+    const VERSION: u8 = Project::VERSION;
+
+    type Legacy = ProjectV0;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Legacy projects tracked a single USDC balance. It's moved into
+    /// a mint balance by `migrate_project`, which knows the mint.
+    fn from_legacy(legacy: ProjectV0) -> Self {
+        Self {
+            version: Project::VERSION,
+            project_id: legacy.project_id,
+            name: legacy.name,
+            raised: legacy.raised,
+            goal: legacy.goal,
+            balance: legacy.balance,
+            contributors: legacy.contributors,
+            admins: legacy.admins,
+            beneficiary: legacy.beneficiary,
+            bump: legacy.bump,
+            status: legacy.status,
+            campaign: None,
+            balances: vec![],
+            rent_payer: Pubkey::default(),
+            metadata: Metadata::default(),
+            goal_met: None,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MilestoneV0 {
+    pub milestone_id: u64,
+    pub name: String,
+    pub percentage: f64,
+    pub close: u64,
+    pub associated_project: Pubkey,
+    pub bump: u8,
+    pub status: MilestoneStatus,
+}
+
+impl Migrate for Milestone {
+    const VERSION: u8 = Milestone::VERSION;
+
+    type Legacy = MilestoneV0;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn from_legacy(legacy: MilestoneV0) -> Self {
+        Self {
+            version: Milestone::VERSION,
+            milestone_id: legacy.milestone_id,
+            name: legacy.name,
+            percentage: legacy.percentage,
+            close: legacy.close,
+            associated_project: legacy.associated_project,
+            bump: legacy.bump,
+            status: legacy.status,
+            rent_payer: Pubkey::default(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FundingSourceV0 {
+    pub name: String,
+    pub authority: Pubkey,
+    pub pool_id: u64,
+    pub amount: u64,
+    pub bump: u8,
+}
+
+impl Migrate for FundingSource {
+    const VERSION: u8 = FundingSource::VERSION;
+
+    type Legacy = FundingSourceV0;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn from_legacy(legacy: FundingSourceV0) -> Self {
+        Self {
+            version: FundingSource::VERSION,
+            name: legacy.name,
+            authority: legacy.authority,
+            pool_id: legacy.pool_id,
+            amount: legacy.amount,
+            bump: legacy.bump,
+            // Legacy sources didn't track deposits, so
+            // the whole commitment is taken as deposited
+            deposited: legacy.amount,
+            refunded: vec![],
+            earmark: Earmark::Unrestricted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{to_pubkey, USDC_DEVNET_MINT, USDC_MINT};
+
+    /// Builds account data byte by byte, so fixtures don't
+    /// depend on the structs they're checked against.
+    struct Fixture(Vec<u8>);
+
+    impl Fixture {
+        fn new(discriminator: [u8; 8]) -> Self {
+            Self(discriminator.to_vec())
+        }
+        fn u8(mut self, value: u8) -> Self {
+            self.0.push(value);
+            self
+        }
+        fn u64(mut self, value: u64) -> Self {
+            self.0.extend_from_slice(&value.to_le_bytes());
+            self
+        }
+        fn f64(mut self, value: f64) -> Self {
+            self.0.extend_from_slice(&value.to_le_bytes());
+            self
+        }
+        fn len(mut self, value: u32) -> Self {
+            self.0.extend_from_slice(&value.to_le_bytes());
+            self
+        }
+        fn string(self, value: &str) -> Self {
+            let mut fixture = self.len(value.len() as u32);
+            fixture.0.extend_from_slice(value.as_bytes());
+            fixture
+        }
+        fn pubkey(mut self, value: &Pubkey) -> Self {
+            self.0.extend_from_slice(value.as_ref());
+            self
+        }
+        /// Zero padding left over from the legacy `SPACE`
+        fn pad(mut self, space: usize) -> Vec<u8> {
+            self.0.resize(space.max(self.0.len()), 0);
+            self.0
+        }
+    }
+
+    #[test]
+    fn try_migrate_pool_v0() -> Result<()> {
+        let admin = Pubkey::new_unique();
+        let project = Pubkey::new_unique();
+        let donor = Pubkey::new_unique();
+        let funder = Pubkey::new_unique();
+        let devnet_usdc = to_pubkey(USDC_DEVNET_MINT);
+        let usdc = to_pubkey(USDC_MINT);
+
+        // Pool id 1 puts a 1 where the version byte now lives
+        let fixture = |claimed: u8| Fixture::new(Pool::DISCRIMINATOR)
+            .u64(1)                             // pool_id
+            .string("Round 1")                  // name
+            .u64(5_000)                         // total_funding
+            .u64(5_000)                         // balance
+            .u64(100)                           // start
+            .u64(200)                           // end
+            .len(1).pubkey(&admin)              // admins
+            .len(1)                             // project_shares
+                .pubkey(&project).u8(claimed)   // project_key, claimed
+                .f64(1.0)                       // share
+                .len(1)                         // votes
+                    .pubkey(&donor).u8(1).pubkey(&usdc).u64(250)
+            .len(1)                             // funders: the wallet, in devnet USDC
+                .pubkey(&funder).u8(1).pubkey(&devnet_usdc).u64(5_000)
+            .u8(3)                              // pool_state: Closed
+            .u8(0)                              // pool_access: Open
+            .u8(254)                            // bump
+            .pad(2_861);
+
+        let mut pool: Pool = migrate_account_data(&fixture(1))?;
+        assert_eq!(pool.version, Pool::VERSION);
+        assert_eq!(pool.pool_id, 1);
+        assert_eq!(pool.name, "Round 1");
+        assert_eq!(pool.end, 200);
+        assert_eq!(pool.admins, vec![admin]);
+        assert_eq!(pool.project_shares[0].project_key, project);
+        assert!(pool.project_shares[0].claimed);
+        assert_eq!(pool.project_shares[0].share_data.votes[0].amount, 250);
+        assert_eq!(pool.project_shares[0].category, None);
+        assert!(pool.funders[0].earmark == Earmark::Unrestricted);
+        assert_eq!(pool.pool_state, PoolState::Distributed);
+
+        // Tickets are moved onto the wallet's source, in the vault's mint
+        let source_key = Pubkey::new_unique();
+        let sources = vec![(
+            source_key,
+            FundingSource::new("Sponsor".to_owned(), funder, 1, 5_000, Earmark::Unrestricted, 255)?,
+        )];
+        assert!(rebind_legacy_funders(&mut pool.clone(), usdc, &[]).is_err());
+        rebind_legacy_funders(&mut pool, usdc, &sources)?;
+        assert_eq!(pool.funders[0].source, source_key);
+        assert_eq!(pool.funders[0].mint, Some(usdc));
+        assert_eq!(pool.calculate_payouts(&project)?, vec![MintAmount { mint: usdc, amount: 5_000 }]);
+        assert_eq!(pool.project_shares[0].paid, vec![MintAmount { mint: usdc, amount: 5_000 }]);
+        assert!(pool.is_settled(&pool.project_shares[0]));
+        assert!(pool.pool_access == PoolAccess::Open);
+        assert_eq!(pool.bump, 254);
+
+        // A closed pool nobody claimed from was cancelled,
+        // and its funders can be refunded through their source
+        let mut unclaimed: Pool = migrate_account_data(&fixture(0))?;
+        assert_eq!(unclaimed.pool_state, PoolState::Cancelled);
+        rebind_legacy_funders(&mut unclaimed, usdc, &sources)?;
+        assert!(unclaimed.project_shares[0].paid.is_empty());
+        assert_eq!(unclaimed.remove_source(&source_key, &usdc), 5_000);
+
+        // A migrated pool reads back as current, and can't be migrated twice
+        let mut migrated = vec![];
+        pool.try_serialize(&mut migrated)?;
+        let reloaded = Pool::try_deserialize(&mut &migrated[..])?;
+        assert_eq!(reloaded.pool_id, 1);
+        assert!(migrate_account_data::<Pool>(&migrated).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_migrate_project_v0() -> Result<()> {
+        let admin = Pubkey::new_unique();
+        let beneficiary = Pubkey::new_unique();
+
+        let data = Fixture::new(Project::DISCRIMINATOR)
+            .u64(7)                             // project_id
+            .string("Stockpile")                // name
+            .u64(1_500_000)                     // raised
+            .u64(10_000_000)                    // goal
+            .u64(1_000_000)                     // balance
+            .u8(3)                              // contributors
+            .len(1).pubkey(&admin)              // admins
+            .pubkey(&beneficiary)               // beneficiary
+            .u8(255)                            // bump
+            .u8(1)                              // status: Deactivated
+            .pad(576);
+
+        let project: Project = migrate_account_data(&data)?;
+        assert_eq!(project.version, Project::VERSION);
+        assert_eq!(project.project_id, 7);
+        assert_eq!(project.raised, 1_500_000);
+        assert_eq!(project.goal, 10_000_000);
+        assert_eq!(project.balance, 1_000_000);
+        assert_eq!(project.contributors, 3);
+        assert_eq!(project.beneficiary, beneficiary);
+        assert!(project.status == ProjectStatus::Deactivated);
+        assert!(project.campaign.is_none());
+        assert!(project.balances.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_migrate_milestone_v0() -> Result<()> {
+        let project = Pubkey::new_unique();
+
+        let data = Fixture::new(Milestone::DISCRIMINATOR)
+            .u64(2)                             // milestone_id
+            .string("Audit")                    // name
+            .f64(0.25)                          // percentage
+            .u64(0)                             // close
+            .pubkey(&project)                   // associated_project
+            .u8(253)                            // bump
+            .u8(1)                              // status: Reconciling
+            .pad(467);
+
+        let milestone: Milestone = migrate_account_data(&data)?;
+        assert_eq!(milestone.version, Milestone::VERSION);
+        assert_eq!(milestone.milestone_id, 2);
+        assert_eq!(milestone.percentage, 0.25);
+        assert_eq!(milestone.associated_project, project);
+        assert!(milestone.status == MilestoneStatus::Reconciling);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_migrate_source_v0() -> Result<()> {
+        let authority = Pubkey::new_unique();
+
+        let data = Fixture::new(FundingSource::DISCRIMINATOR)
+            .string("Sponsor")                  // name
+            .pubkey(&authority)                 // authority
+            .u64(1)                             // pool_id
+            .u64(5_000)                         // amount
+            .u8(252)                            // bump
+            .pad(169);
+
+        let source: FundingSource = migrate_account_data(&data)?;
+        assert_eq!(source.version, FundingSource::VERSION);
+        assert_eq!(source.name, "Sponsor");
+        assert_eq!(source.authority, authority);
+        assert_eq!(source.amount, 5_000);
+        assert_eq!(source.bump, 252);
+
+        // The whole commitment is taken as deposited, so it can be refunded
+        let mut source = source;
+        source.withdraw(5_000)?;
+        assert!(source.withdraw(1).is_err());
+        assert!(source.earmark == Earmark::Unrestricted);
+
+        // Data for a different account type is rejected
+        assert!(migrate_account_data::<Pool>(&data).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ProtocolError, util::MAX_URI_LEN};
+
+/// Points to an off-chain document describing a project or pool,
+/// following `schemas/metadata.schema.json`. The hash is the SHA-256
+/// of the document, so clients can verify what the URI serves.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct Metadata {
+    pub uri: String,
+    pub hash: [u8; 32],
+    /// Bumped on every update, so clients can detect stale caches
+    pub revision: u32,
+}
+
+impl Metadata {
+    pub const SPACE: usize = 4 + MAX_URI_LEN + 32 + 4;
+
+    pub fn update(&mut self, uri: String, hash: [u8; 32]) -> Result<()> {
+        require!(uri.as_bytes().len() <= MAX_URI_LEN, ProtocolError::UriTooLong);
+        self.uri = uri;
+        self.hash = hash;
+        self.revision = self.revision
+            .checked_add(1)
+            .ok_or(ProtocolError::AlgorithmFailure)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_update_metadata() -> Result<()> {
+        let mut metadata = Metadata::default();
+        metadata.update("https://example.com/project.json".to_string(), [1; 32])?;
+        metadata.update("ipfs://bafy/project.json".to_string(), [2; 32])?;
+        assert_eq!(metadata.revision, 2);
+        assert_eq!(metadata.hash, [2; 32]);
+
+        // A failed update leaves the revision alone
+        assert!(metadata.update("a".repeat(MAX_URI_LEN + 1), [3; 32]).is_err());
+        assert_eq!(metadata.revision, 2);
+        assert_eq!(metadata.uri, "ipfs://bafy/project.json");
+
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@ use crate::{error::ProtocolError, util::MAX_NAME_LEN};
 #[account]
 #[derive(Default)]
 pub struct Milestone {
+    pub version: u8,
     pub milestone_id: u64,
     pub name: String,
     pub percentage: f64,
@@ -12,35 +13,40 @@ pub struct Milestone {
     pub associated_project: Pubkey,
     pub bump: u8,
     pub status: MilestoneStatus,
+    /// Receives the account's rent when it's closed
+    pub rent_payer: Pubkey,
 }
 
 impl Milestone {
     pub const SEED_PREFIX: &'static str = "milestone";
 
-    pub const SPACE: usize = 8 
-        + 4                         // u64
-        + 4 + MAX_NAME_LEN          // String
-        + 32                        // Pubkey
-        + 8                         // u64
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
         + 8                         // u64
+        + 4 + MAX_NAME_LEN          // String
+        + 8                         // f64
         + 8                         // u64
+        + 32                        // Pubkey
         + 1                         // u8
-        + 1                         // u8
-        + 1 + 42                    // Enum
-        + 250;                      // Padding
+        + 1                         // Enum (Singleton)
+        + 32;                       // Pubkey                      // Padding
 
-    pub fn new(milestone_id: u64, name: String, percentage: f64, associated_project: Pubkey, bump: u8) -> Result<Self> {
+    pub fn new(milestone_id: u64, name: String, percentage: f64, associated_project: Pubkey, rent_payer: Pubkey, bump: u8) -> Result<Self> {
         if name.as_bytes().len() > MAX_NAME_LEN {
             return Err(ProtocolError::NameTooLong.into());
         }
 
         Ok(Self {
+            version: Self::VERSION,
             milestone_id,
             name,
             percentage,
             close: 0,
             associated_project,
             bump,
+            rent_payer,
             ..Default::default()
         })
     }
@@ -1,4 +1,12 @@
 pub mod pool;
 pub mod project;
 pub mod source;
-pub mod milestone;
\ No newline at end of file
+pub mod milestone;
+pub mod subscription;
+pub mod receipt;
+pub mod legacy;
+pub mod config;
+pub mod creator;
+pub mod metadata;
+pub mod registry;
+pub mod series;
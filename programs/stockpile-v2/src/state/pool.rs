@@ -1,15 +1,17 @@
 use anchor_lang::prelude::*;
-use pyth_sdk_solana::load_price_feed_from_account_info;
 use std::collections::BTreeMap;
 
 use crate::{
     error::ProtocolError,
-    util::{to_pubkey, MAX_NAME_LEN, USDC_MINT, USDC_DEVNET_MINT},
+    oracle::OracleConfig,
+    state::{metadata::Metadata, source::Earmark},
+    util::{MAX_CATEGORIES, MAX_NAME_LEN, MAX_POOL_DURATION, MAX_POOL_TRANSITIONS, MIN_CLAIM_WINDOW, MIN_POOL_DURATION},
 };
 
 #[account]
 #[derive(Default)]
 pub struct Pool {
+    pub version: u8,
     pub pool_id: u64,
     pub name: String,
     pub total_funding: u64,
@@ -22,34 +24,64 @@ pub struct Pool {
     pub pool_state: PoolState,
     pub pool_access: PoolAccess,
     pub bump: u8,
+    pub caps: MatchingCaps,
+    pub categories: Vec<Category>,
+    pub oracle: OracleConfig,
+    pub history: Vec<StateTransition>,
+    pub soft_close: Option<SoftClose>,
+    /// Total time the round has been extended by the soft close
+    pub extended_by: u64,
+    /// Rent paid into the account, returned when the pool is closed.
+    /// Rent funded after the pool was created is credited to its creator.
+    pub rent_payers: Vec<RentPayment>,
+    pub metadata: Metadata,
+    /// What happens to payouts left unclaimed after a deadline
+    pub unclaimed: Option<UnclaimedPolicy>,
+    /// Matching returned by the pools it was rolled into,
+    /// which funders can withdraw as unspent
+    pub returned: Vec<MintAmount>,
 }
 
 impl Pool {
     pub const SEED_PREFIX: &'static str = "pool";
 
+    pub const VERSION: u8 = 1;
+
     pub const SPACE: usize = 8
-        + 4                         // u64
+        + 1                         // u8
+        + 8                         // u64
         + 4 + MAX_NAME_LEN          // String
-        + 4                         // u64
-        + 4                         // u64
-        + 4                         // u64
-        + 160                       // Vec<Pubkey> (Max 5)
-        + 32                        // Vec<Participants> (Initial Alloc. for 10 participants w/ 20 votes)
-        + 32                        // Vec<FundingTicket> (Initial Alloc. for 5)
-        + 4                         // Enum (singleton)
-        + 4                         // Enum (singleton)
+        + 8                         // u64
+        + 8                         // u64
+        + 8                         // u64
+        + 8                         // u64
+        + 4 + (32 * 5)              // Vec<Pubkey> (Initial Alloc. for 5)
+        + 4                         // Vec<Participant>
+        + 4                         // Vec<FundingTicket>
+        + 1                         // Enum (singleton)
+        + 1                         // Enum (singleton)
         + 1                         // u8
-        + 2500;                     // Padding
-
-    pub fn new(pool_id: u64, name: String, start: u64, end: u64, admins: Vec<Pubkey>, access: PoolAccess, bump: u8) -> Result<Self> {
+        + MatchingCaps::SPACE       // MatchingCaps
+        + 4 + (Category::SPACE * MAX_CATEGORIES) // Vec<Category>
+        + OracleConfig::SPACE       // OracleConfig
+        + 4 + (StateTransition::SPACE * MAX_POOL_TRANSITIONS) // Vec<StateTransition>
+        + 1 + SoftClose::SPACE      // Option<SoftClose>
+        + 8                         // u64
+        + 4 + RentPayment::SPACE    // Vec<RentPayment> (Initial Alloc. for the creator)
+        + Metadata::SPACE           // Metadata
+        + 1 + UnclaimedPolicy::SPACE // Option<UnclaimedPolicy>
+        + 4                         // Vec<MintAmount>
+        + 2500;                     // Room for participants, votes and funders before a realloc
+
+    pub fn new(pool_id: u64, name: String, start: u64, end: u64, admins: Vec<Pubkey>, access: PoolAccess, caps: MatchingCaps, bump: u8) -> Result<Self> {
         if name.as_bytes().len() > MAX_NAME_LEN {
             return Err(ProtocolError::NameTooLong.into());
         }
+        caps.validate()?;
         let current_time = Clock::get()?.unix_timestamp as u64;
-        if current_time > start {
-            return Err(ProtocolError::PoolInvalidStart.into());
-        }
+        validate_schedule(start, end, current_time)?;
         Ok(Self {
+            version: Self::VERSION,
             pool_id,
             name,
             total_funding: 0,
@@ -62,81 +94,260 @@ impl Pool {
             pool_state: PoolState::PendingStart,
             pool_access: access,
             bump,
+            caps,
+            categories: vec![],
+            oracle: OracleConfig::default(),
+            history: vec![],
+            soft_close: None,
+            extended_by: 0,
+            rent_payers: vec![],
+            metadata: Metadata::default(),
+            unclaimed: None,
+            returned: vec![],
         })
     }
 
-    pub fn close_pool(&mut self) -> Result<()> {
-        self.is_active()?;
-        self.pool_state = PoolState::Closed;
-        Ok(())
+    /// Returns the state the pool is in at `now`. The time-driven
+    /// transitions (into `Active` and `Ended`) are applied here without
+    /// being written, so checks never mutate the pool.
+    pub fn state_at(&self, now: u64) -> PoolState {
+        let mut state = self.pool_state.clone();
+        if state == PoolState::PendingStart && now > self.start {
+            state = PoolState::Active;
+        }
+        if state == PoolState::Active && now > self.end {
+            state = PoolState::Ended;
+        }
+        state
     }
 
-    pub fn is_active(&mut self) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
-        if current_time > self.end {
-            return Err(ProtocolError::EndDatePassed.into());
+    /// Writes any time-driven transitions that have
+    /// happened since the pool was last synced.
+    pub fn sync_state(&mut self, now: u64) -> Result<()> {
+        if self.pool_state == PoolState::PendingStart && now > self.start {
+            self.transition(PoolState::Active, self.start)?;
         }
-        if current_time > self.start {
-            self.pool_state = PoolState::Active;
+        if self.pool_state == PoolState::Active && now > self.end {
+            self.transition(PoolState::Ended, self.end)?;
         }
-        match self.pool_state {
-            PoolState::PendingStart => Err(ProtocolError::PoolNotStarted.into()),
-            PoolState::Active => Ok(()),
-            PoolState::Distributed => Err(ProtocolError::ReleasedFunds.into()),
-            PoolState::Closed => Err(ProtocolError::PoolClosed.into()),
+        Ok(())
+    }
+
+    /// Space still needed for the transitions the pool can make.
+    /// History is written without a realloc, so resizes keep this free.
+    pub fn transition_room(&self) -> usize {
+        StateTransition::SPACE * MAX_POOL_TRANSITIONS.saturating_sub(self.history.len())
+    }
+
+    /// Moves the pool to `next` if the transition table allows
+    /// it, and records the transition in the pool's history.
+    pub fn transition(&mut self, next: PoolState, timestamp: u64) -> Result<()> {
+        require!(
+            self.pool_state.can_transition_to(&next),
+            ProtocolError::InvalidStateTransition
+        );
+        self.history.push(StateTransition {
+            from: self.pool_state.clone(),
+            to: next.clone(),
+            timestamp,
+        });
+        self.pool_state = next;
+        Ok(())
+    }
+
+    /// Freezes the pool's shares once the round has ended,
+    /// so payouts can be claimed.
+    pub fn finalize(&mut self, now: u64) -> Result<()> {
+        self.sync_state(now)?;
+        require!(self.pool_state == PoolState::Ended, ProtocolError::PoolNotEnded);
+        self.transition(PoolState::Finalized, now)
+    }
+
+    /// Moves both the start and end of a pool that hasn't started yet
+    pub fn reschedule(&mut self, start: u64, end: u64, now: u64) -> Result<()> {
+        require!(self.state_at(now) == PoolState::PendingStart, ProtocolError::PoolAlreadyStarted);
+        validate_schedule(start, end, now)?;
+        self.start = start;
+        self.end = end;
+        Ok(())
+    }
+
+    /// Ends an active round ahead of schedule. The round must
+    /// have run for at least the minimum duration, so a pool
+    /// can't be closed before donors have had a chance to vote.
+    pub fn close_early(&mut self, now: u64) -> Result<()> {
+        self.sync_state(now)?;
+        require!(self.pool_state == PoolState::Active, ProtocolError::PoolNotActive);
+        require!(now >= self.start + MIN_POOL_DURATION, ProtocolError::PoolInvalidDuration);
+        self.end = now;
+        self.transition(PoolState::Ended, now)
+    }
+
+    /// Snapshot of the round's shares and vote count
+    pub fn activity(&self) -> RoundActivity {
+        RoundActivity {
+            shares: self.project_shares.iter().map(|p| p.share_data.share).collect(),
+            votes: self.project_shares.iter().map(|p| p.share_data.votes.len()).sum(),
         }
     }
 
-    pub fn can_fund(&mut self) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
-        if current_time > self.end {
-            return Err(ProtocolError::EndDatePassed.into());
+    /// Extends the round if a vote in the soft close window moved the
+    /// round by more than the configured threshold, compared to `before`.
+    /// Returns the new end date if the round was extended.
+    pub fn apply_soft_close(&mut self, before: &RoundActivity, now: u64) -> Option<u64> {
+        let soft_close = self.soft_close.clone()?;
+        if now > self.end || now < self.end.saturating_sub(soft_close.window) {
+            return None;
         }
-        if current_time > self.start {
-            self.pool_state = PoolState::Active;
+        if self.activity().movement_from(before) <= soft_close.threshold {
+            return None;
         }
-        match self.pool_state {
-            PoolState::PendingStart => Ok(()),
-            PoolState::Active => Ok(()),
-            PoolState::Distributed => Err(ProtocolError::ReleasedFunds.into()),
-            PoolState::Closed => Err(ProtocolError::PoolClosed.into()),
+
+        let extension = soft_close.extension
+            .min(soft_close.max_extension.saturating_sub(self.extended_by));
+        if extension == 0 {
+            return None;
         }
+
+        self.end += extension;
+        self.extended_by += extension;
+        Some(self.end)
     }
 
-    pub fn can_withdraw(&mut self) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp as u64;
-        if current_time > self.start {
-            self.pool_state = PoolState::Active;
+    /// Records rent paid into the pool by `payer`, so it can be returned
+    /// on close. Recording zero reserves the payer's entry, so the account
+    /// can be sized with it before the rent is known.
+    pub fn record_rent(&mut self, payer: Pubkey, lamports: u64) {
+        match self.rent_payers.iter_mut().find(|r| r.payer == payer) {
+            Some(payment) => payment.lamports += lamports,
+            None => self.rent_payers.push(RentPayment { payer, lamports }),
         }
+    }
 
-        if self.project_shares.len() == 0 && current_time > self.end {
-            return Ok(());
+    /// A pool can only be closed once it's been fully paid out, or
+    /// cancelled with every source and refundable vote returned
+    pub fn can_close(&self) -> Result<()> {
+        match self.pool_state {
+            PoolState::Distributed => Ok(()),
+            PoolState::Cancelled => {
+                let refundable_votes = self.project_shares
+                    .iter()
+                    .any(|p| p.refund_votes && !p.share_data.votes.is_empty());
+                require!(
+                    self.funders.is_empty() && !refundable_votes,
+                    ProtocolError::PoolNotSettled
+                );
+                Ok(())
+            },
+            _ => Err(ProtocolError::PoolNotSettled.into()),
         }
+    }
 
-        let mut sum: f64 = 0.0;
-        for share in &self.project_shares {
-            sum += share.share_data.share;
-        };
+    /// Whether a source has nothing left in the pool in any mint,
+    /// either because it was refunded or because it was paid out.
+    pub fn is_source_settled(&self, source: &Pubkey, refunded: &[MintAmount]) -> bool {
+        if !self.funders.iter().any(|t| t.source == *source) {
+            return true;
+        }
+        self.pool_state == PoolState::Distributed
+            && self.funding_mints().iter().all(|mint| {
+                MintAmount::amount_in(refunded, mint)
+                    >= self.calculate_unspent_for_source(source, mint)
+            })
+    }
+
+    /// Cancels the pool. Only rounds that haven't been finalized can be cancelled.
+    pub fn cancel(&mut self, now: u64) -> Result<()> {
+        self.sync_state(now)?;
+        self.transition(PoolState::Cancelled, now)
+    }
+
+    /// Lets donors reclaim their votes for a project in a cancelled pool
+    pub fn enable_vote_refunds(&mut self, project_key: &Pubkey) -> Result<()> {
+        require!(self.pool_state == PoolState::Cancelled, ProtocolError::PoolNotCancelled);
+        let participant = self.project_shares
+            .iter_mut()
+            .find(|p| p.project_key == *project_key)
+            .ok_or(ProtocolError::NotInPool)?;
+        participant.refund_votes = true;
+        Ok(())
+    }
+
+    /// Removes a donor's votes in `mint` for a project that has enabled
+    /// vote refunds, returning the amount owed back to the donor.
+    pub fn take_vote_refund(&mut self, project_key: &Pubkey, payer: &Pubkey, mint: &Pubkey) -> Result<u64> {
+        require!(self.pool_state == PoolState::Cancelled, ProtocolError::PoolNotCancelled);
+        let participant = self.project_shares
+            .iter_mut()
+            .find(|p| p.project_key == *project_key)
+            .ok_or(ProtocolError::NotInPool)?;
+        require!(participant.refund_votes, ProtocolError::VoteRefundsDisabled);
+
+        let is_refunded = |t: &VoteTicket| t.payer == *payer && t.mint == Some(*mint);
+        let refund: u64 = participant.share_data.votes
+            .iter()
+            .filter(|t| is_refunded(t))
+            .map(|t| t.amount)
+            .sum();
+        require!(refund > 0, ProtocolError::NothingToRefund);
+
+        participant.share_data.votes.retain(|t| !is_refunded(t));
+
+        Ok(refund)
+    }
 
-        if sum == 0.0 && current_time > self.end {
-            return Ok(());
+    /// Marks the pool as distributed once every participant
+    /// has been paid in full, or has forfeited their payout.
+    pub fn maybe_mark_distributed(&mut self, now: u64) -> Result<()> {
+        let settled = self.project_shares
+            .iter()
+            .all(|p| self.is_settled(p));
+        if self.pool_state == PoolState::Finalized && settled {
+            self.transition(PoolState::Distributed, now)?;
         }
+        Ok(())
+    }
 
-        match self.pool_state {
+    pub fn is_active(&self) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        match self.state_at(current_time) {
+            PoolState::PendingStart => Err(ProtocolError::PoolNotStarted.into()),
+            PoolState::Active => Ok(()),
+            PoolState::Ended | PoolState::Finalized => Err(ProtocolError::EndDatePassed.into()),
+            PoolState::Distributed => Err(ProtocolError::ReleasedFunds.into()),
+            PoolState::Cancelled => Err(ProtocolError::PoolClosed.into()),
+        }
+    }
+
+    pub fn can_fund(&self) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        match self.state_at(current_time) {
             PoolState::PendingStart => Ok(()),
-            PoolState::Active => Err(ProtocolError::PoolStillActive.into()),
+            PoolState::Active => Ok(()),
+            PoolState::Ended | PoolState::Finalized => Err(ProtocolError::EndDatePassed.into()),
             PoolState::Distributed => Err(ProtocolError::ReleasedFunds.into()),
-            PoolState::Closed => Err(ProtocolError::PoolClosed.into()),
+            PoolState::Cancelled => Err(ProtocolError::PoolClosed.into()),
         }
     }
 
-    /// Calculates the total funding amount from current Pyth price data
-    pub fn calculate_pool_total_usd(&self, usdc_usd_price: f64) -> Result<f64> {
+    pub fn can_claim(&self) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        match self.state_at(current_time) {
+            PoolState::PendingStart | PoolState::Active => Err(ProtocolError::PoolStillActive.into()),
+            PoolState::Ended => Err(ProtocolError::PoolNotFinalized.into()),
+            PoolState::Finalized => Ok(()),
+            PoolState::Distributed => Err(ProtocolError::ReleasedFunds.into()),
+            PoolState::Cancelled => Err(ProtocolError::PoolClosed.into()),
+        }
+    }
+
+    /// Calculates the total funding amount from current oracle prices
+    pub fn calculate_pool_total_usd(&self, prices: &BTreeMap<Pubkey, f64>) -> Result<f64> {
         let mut pool_total_usd: f64 = 0.0;
 
         for ticket in &self.funders {
-            pool_total_usd +=
-                calculate_price_usd(ticket.mint, ticket.amount, usdc_usd_price)?;
+            let mint = ticket.mint.ok_or(ProtocolError::MintNotSupported)?;
+            pool_total_usd += self.oracle.value_usd(&mint, ticket.amount, prices)?;
         }
 
         msg!("Pool total USD: {}", pool_total_usd);
@@ -144,51 +355,504 @@ impl Pool {
         Ok(pool_total_usd)
     }
 
-    pub fn add_participant(&mut self, project_key: Pubkey) -> Result<()> {
+    pub fn add_participant(&mut self, project_key: Pubkey, category: Option<u8>) -> Result<()> {
+        self.validate_category(category)?;
+
         self.project_shares.push(
             Participant::new(
                 project_key, 
                 PoolShare::new(),
+                category,
             )
         );
 
         Ok(())
     }
 
+    /// Records a deposit from a `FundingSource`. Deposits from the
+    /// same source and mint are merged into a single ticket.
+    pub fn record_funding(&mut self, source: Pubkey, mint: Option<Pubkey>, amount: u64, earmark: Earmark) -> Result<()> {
+        if let Earmark::Category(index) = earmark {
+            self.validate_category(Some(index))?;
+        }
+
+        if let Some(ticket) = self.funders.iter_mut().find(|t| t.source == source && t.mint == mint) {
+            ticket.amount = ticket.amount
+                .checked_add(amount)
+                .ok_or(ProtocolError::AlgorithmFailure)?;
+            ticket.earmark = earmark;
+        } else {
+            self.funders.push(FundingTicket::new(source, mint, amount, earmark));
+        }
+
+        self.total_funding = self.total_funding
+            .checked_add(amount)
+            .ok_or(ProtocolError::AlgorithmFailure)?;
+
+        Ok(())
+    }
+
+    /// Removes a source's tickets in `mint`, returning the amount
+    /// it had deposited in it. Only valid before payouts begin.
+    pub fn remove_source(&mut self, source: &Pubkey, mint: &Pubkey) -> u64 {
+        let is_removed = |t: &FundingTicket| t.source == *source && t.mint == Some(*mint);
+        let removed: u64 = self.funders
+            .iter()
+            .filter(|t| is_removed(t))
+            .map(|t| t.amount)
+            .sum();
+
+        self.funders.retain(|t| !is_removed(t));
+        self.total_funding -= removed;
+
+        removed
+    }
+
+    /// Sum of the shares of every participant an earmark can pay out to
+    fn eligible_share(&self, earmark: &Earmark) -> f64 {
+        self.project_shares
+            .iter()
+            .filter(|p| earmark.is_eligible(p))
+            .map(|p| self.effective_share(p))
+            .sum()
+    }
+
+    /// Portion of an unrestricted ticket paid out to a category.
+    /// Pools without categories pay everything to a single group.
+    fn category_allocation(&self, category: Option<u8>) -> f64 {
+        match category {
+            Some(index) => self.categories
+                .get(index as usize)
+                .map(|c| c.allocation)
+                .unwrap_or(0.0),
+            None => 1.0,
+        }
+    }
+
+    /// The amount of a funding ticket paid out to a participant.
+    /// Shares are relative to the participant's category, so a ticket pays
+    /// each category its allocation, and each participant its share of that.
+    /// Tickets earmarked for participants are split between them by share.
+    fn ticket_payout(&self, ticket: &FundingTicket, participant: &Participant) -> f64 {
+        if !ticket.earmark.is_eligible(participant) {
+            return 0.0;
+        }
+
+        let share = self.effective_share(participant);
+
+        match &ticket.earmark {
+            Earmark::Unrestricted => {
+                ticket.amount as f64 * self.category_allocation(participant.category) * share
+            },
+            Earmark::Category(_) => ticket.amount as f64 * share,
+            Earmark::Participants(_) => {
+                let eligible_share = self.eligible_share(&ticket.earmark);
+                if eligible_share > 0.0 {
+                    ticket.amount as f64 * share / eligible_share
+                } else {
+                    0.0
+                }
+            },
+        }
+    }
+
+    /// Every mint the pool has been funded in
+    pub fn funding_mints(&self) -> Vec<Pubkey> {
+        let mut mints: Vec<Pubkey> = vec![];
+        for mint in self.funders.iter().filter_map(|t| t.mint) {
+            if !mints.contains(&mint) {
+                mints.push(mint);
+            }
+        }
+        mints
+    }
+
+    /// Calculates a participant's matching payout in `mint`
+    /// from the funding tickets in that mint
+    pub fn calculate_payout(&self, project_key: &Pubkey, mint: &Pubkey) -> Result<u64> {
+        let participant = self.project_shares
+            .iter()
+            .find(|p| p.project_key == *project_key)
+            .ok_or(ProtocolError::NotInPool)?;
+
+        Ok(self.payout_in(participant, mint))
+    }
+
+    /// Calculates a participant's matching payout in every mint the pool was funded in
+    pub fn calculate_payouts(&self, project_key: &Pubkey) -> Result<Vec<MintAmount>> {
+        let participant = self.project_shares
+            .iter()
+            .find(|p| p.project_key == *project_key)
+            .ok_or(ProtocolError::NotInPool)?;
+
+        Ok(self.funding_mints()
+            .into_iter()
+            .map(|mint| MintAmount { amount: self.payout_in(participant, &mint), mint })
+            .collect())
+    }
+
+    fn payout_in(&self, participant: &Participant, mint: &Pubkey) -> u64 {
+        let payout: f64 = self.funders
+            .iter()
+            .filter(|ticket| ticket.mint == Some(*mint))
+            .map(|ticket| self.ticket_payout(ticket, participant))
+            .sum();
+        payout as u64
+    }
+
+    /// What's left to pay a participant in `mint`. Once claimed, this
+    /// is only ever more than zero when the participant hasn't claimed
+    /// in that mint yet, or when forfeited payouts are redistributed.
+    pub fn claimable(&self, participant: &Participant, mint: &Pubkey) -> u64 {
+        self.payout_in(participant, mint)
+            .saturating_sub(MintAmount::amount_in(&participant.paid, mint))
+    }
+
+    /// Whether a participant has claimed, or forfeited, and has nothing
+    /// left to pay out in any mint. Forfeited payouts that roll over
+    /// are only settled once they've been moved to the next pool.
+    pub fn is_settled(&self, participant: &Participant) -> bool {
+        (participant.claimed || participant.forfeited)
+            && self.funding_mints().iter().all(|mint| self.claimable(participant, mint) == 0)
+    }
+
+    /// Marks a participant's payout in `mint` as claimed, returning
+    /// what's owed. First claims have to be made before the claim deadline.
+    pub fn claim(&mut self, project_key: &Pubkey, mint: &Pubkey, now: u64) -> Result<u64> {
+        let deadline = self.claim_deadline();
+        let participant = self.project_shares
+            .iter()
+            .find(|p| p.project_key == *project_key)
+            .ok_or(ProtocolError::NotInPool)?;
+        require!(!participant.forfeited, ProtocolError::ClaimExpired);
+        if !participant.claimed {
+            require!(deadline.map_or(true, |d| now <= d), ProtocolError::ClaimExpired);
+        }
+
+        let amount = self.claimable(participant, mint);
+        require!(!participant.claimed || amount > 0, ProtocolError::AlreadyClaimed);
+
+        let participant = self.project_shares
+            .iter_mut()
+            .find(|p| p.project_key == *project_key)
+            .ok_or(ProtocolError::NotInPool)?;
+        participant.claimed = true;
+        MintAmount::add(&mut participant.paid, *mint, amount)?;
+        Ok(amount)
+    }
+
+    /// When first claims close, if the pool has an `UnclaimedPolicy`
+    pub fn claim_deadline(&self) -> Option<u64> {
+        let policy = self.unclaimed.as_ref()?;
+        let finalized_at = self.history
+            .iter()
+            .find(|t| t.to == PoolState::Finalized)?
+            .timestamp;
+        Some(finalized_at.saturating_add(policy.window))
+    }
+
+    /// Forfeits every payout still unclaimed after the deadline,
+    /// returning their total in each mint. Forfeited payouts are then
+    /// paid out according to the pool's `UnclaimedPolicy`.
+    pub fn expire_claims(&mut self, now: u64) -> Result<Vec<MintAmount>> {
+        require!(self.pool_state == PoolState::Finalized, ProtocolError::PoolNotFinalized);
+        let deadline = self.claim_deadline().ok_or(ProtocolError::NoClaimDeadline)?;
+        require!(now > deadline, ProtocolError::ClaimDeadlineNotPassed);
+
+        let is_expiring = |p: &Participant| !p.claimed && !p.forfeited;
+        let forfeited = self.funding_mints()
+            .into_iter()
+            .map(|mint| MintAmount {
+                amount: self.project_shares
+                    .iter()
+                    .filter(|p| is_expiring(p))
+                    .map(|p| self.claimable(p, &mint))
+                    .sum(),
+                mint,
+            })
+            .collect();
+        for participant in self.project_shares.iter_mut().filter(|p| is_expiring(p)) {
+            participant.forfeited = true;
+        }
+
+        // Without a next round to roll into, funders get it back
+        if let Some(policy) = &mut self.unclaimed {
+            if policy.fate == UnclaimedFate::NextRound {
+                policy.fate = UnclaimedFate::ReturnToFunders;
+            }
+        }
+
+        Ok(forfeited)
+    }
+
+    /// Takes the forfeited payouts in `mint` to roll over. What's taken
+    /// is recorded as paid, so it can only be rolled over once.
+    pub fn take_forfeited(&mut self, mint: &Pubkey) -> Result<u64> {
+        let amounts: Vec<u64> = self.project_shares
+            .iter()
+            .map(|p| match p.forfeited {
+                true => self.claimable(p, mint),
+                false => 0,
+            })
+            .collect();
+
+        let mut taken: u64 = 0;
+        for (participant, amount) in self.project_shares.iter_mut().zip(amounts) {
+            if amount > 0 {
+                MintAmount::add(&mut participant.paid, *mint, amount)?;
+                taken += amount;
+            }
+        }
+        Ok(taken)
+    }
+
+    /// Leaves forfeited payouts for the funders when the pool they were
+    /// meant to roll into can't be funded. Once any have been rolled
+    /// over, the rest have to follow, so the fate is left unchanged.
+    pub fn return_unclaimed_to_funders(&mut self) {
+        let rolled = self.project_shares
+            .iter()
+            .any(|p| p.forfeited && !p.paid.is_empty());
+        if rolled {
+            return;
+        }
+        if let Some(policy) = &mut self.unclaimed {
+            if let UnclaimedFate::Rollover(_) = policy.fate {
+                policy.fate = UnclaimedFate::ReturnToFunders;
+            }
+        }
+    }
+
+    /// Sends payouts left unclaimed to `target`, if they were waiting
+    /// for the series' next round. Fates set by an admin, and those
+    /// settled when claims expired, are left unchanged.
+    pub fn roll_unclaimed_into(&mut self, target: Pubkey) {
+        if let Some(policy) = &mut self.unclaimed {
+            if policy.fate == UnclaimedFate::NextRound {
+                policy.fate = UnclaimedFate::Rollover(target);
+            }
+        }
+    }
+
+    /// A participant's share once claims have expired. Forfeited shares
+    /// pay nothing and, when they're redistributed, are divided among
+    /// the rest of the participant's category in proportion to their
+    /// shares. Rolled over payouts keep their shares, so they still
+    /// count as paid out for the pool's funders.
+    fn effective_share(&self, participant: &Participant) -> f64 {
+        let share = participant.share_data.share;
+        let fate = match &self.unclaimed {
+            Some(policy) => &policy.fate,
+            None => return share,
+        };
+        if let UnclaimedFate::Rollover(_) = fate {
+            return share;
+        }
+        if participant.forfeited {
+            return 0.0;
+        }
+        if *fate == UnclaimedFate::Redistribute {
+            let (total, live) = self.project_shares
+                .iter()
+                .filter(|p| p.category == participant.category)
+                .fold((0.0, 0.0), |(total, live), p| {
+                    let s = p.share_data.share;
+                    (total + s, if p.forfeited { live } else { live + s })
+                });
+            if live > 0.0 {
+                return share * total / live;
+            }
+        }
+        share
+    }
+
+    /// Calculates how much of a source's deposits in `mint` can't be
+    /// paid out, e.g. earmarks for participants that received no votes,
+    /// or matching left unallocated by the caps.
+    pub fn calculate_unspent_for_source(&self, source: &Pubkey, mint: &Pubkey) -> u64 {
+        self.funders
+            .iter()
+            .filter(|t| t.source == *source && t.mint == Some(*mint))
+            .map(|t| self.calculate_unspent_for_ticket(t))
+            .sum()
+    }
+
+    fn calculate_unspent_for_ticket(&self, ticket: &FundingTicket) -> u64 {
+        let distributed: f64 = self.project_shares
+            .iter()
+            .map(|p| self.ticket_payout(ticket, p))
+            .sum();
+        (ticket.amount as f64 - distributed).max(0.0) as u64 + self.returned_for_ticket(ticket)
+    }
+
+    /// A ticket's part of the matching returned to the pool, pro-rata
+    /// to what it funded in the returned mint
+    fn returned_for_ticket(&self, ticket: &FundingTicket) -> u64 {
+        let returned = match ticket.mint {
+            Some(mint) => MintAmount::amount_in(&self.returned, &mint),
+            None => 0,
+        };
+        let funded: u64 = self.funders
+            .iter()
+            .filter(|t| t.mint == ticket.mint)
+            .map(|t| t.amount)
+            .sum();
+        if returned == 0 || funded == 0 {
+            return 0;
+        }
+        (returned as u128 * ticket.amount as u128 / funded as u128) as u64
+    }
+
+    /// Records matching returned by a pool it was rolled into
+    pub fn record_returned(&mut self, mint: Pubkey, amount: u64) -> Result<()> {
+        MintAmount::add(&mut self.returned, mint, amount)
+    }
+
+    /// Takes the unspent matching in `mint` from tickets that were rolled
+    /// in from another pool. These have no `FundingSource` to track refunds,
+    /// so what's been taken is recorded on the tickets themselves.
+    pub fn take_rolled_in_unspent(&mut self, source: &Pubkey, mint: &Pubkey) -> u64 {
+        let unspent: Vec<u64> = self.funders
+            .iter()
+            .map(|t| match t.source == *source && t.mint == Some(*mint) {
+                true => self.calculate_unspent_for_ticket(t).saturating_sub(t.rolled_over),
+                false => 0,
+            })
+            .collect();
+
+        let mut taken = 0;
+        for (ticket, amount) in self.funders.iter_mut().zip(unspent) {
+            ticket.rolled_over += amount;
+            taken += amount;
+        }
+        taken
+    }
+
+    /// Adds a category to the pool. Categories can only be added
+    /// before any participants have entered the pool.
+    pub fn add_category(&mut self, category: Category) -> Result<()> {
+        category.validate()?;
+
+        require!(self.project_shares.is_empty(), ProtocolError::AlreadyEntered);
+        require!(self.categories.len() < MAX_CATEGORIES, ProtocolError::InvalidCategory);
+
+        let allocated: f64 = self.categories.iter().map(|c| c.allocation).sum();
+        require!(allocated + category.allocation <= 1.0, ProtocolError::InvalidCategory);
+
+        self.categories.push(category);
+
+        Ok(())
+    }
+
+    /// Makes sure a participant's category exists. Pools
+    /// with categories require every participant to pick one.
+    fn validate_category(&self, category: Option<u8>) -> Result<()> {
+        match category {
+            Some(index) => require!((index as usize) < self.categories.len(), ProtocolError::InvalidCategory),
+            None => require!(self.categories.is_empty(), ProtocolError::InvalidCategory),
+        }
+        Ok(())
+    }
+
     pub fn extend_pool_duration(&mut self, new_end: u64) -> Result<()> {
         if new_end < self.end {
             return Err(ProtocolError::ExtendDateInvalid.into());
         }
+        require!(new_end - self.start <= MAX_POOL_DURATION, ProtocolError::PoolInvalidDuration);
 
         self.end = new_end;
 
         Ok(())
     }
 
-    /// Updates all shares using the Quadratic Funding algorithm
+    /// Updates all shares using the Quadratic Funding algorithm.
+    /// `oracle_accounts` holds the price accounts for the pool's sources.
     pub fn update_shares(
         &mut self,
-        pyth_usdc_usd: AccountInfo<'_>,
+        oracle_accounts: &[AccountInfo<'_>],
     ) -> Result<()> {
         // Get the current prices for each mint in USD
-        let usdc_usd_price = try_load_price(pyth_usdc_usd)?;
+        let prices = self.oracle.load_prices(&self.vote_mints(), oracle_accounts)?;
+
+        self.calculate_shares(&prices)
+    }
 
-        msg!("USDC/USD Price: {:?}", usdc_usd_price);
+    /// Every mint the pool has received votes in
+    pub fn vote_mints(&self) -> Vec<Pubkey> {
+        self.project_shares
+            .iter()
+            .flat_map(|p| p.share_data.votes.iter().filter_map(|v| v.mint))
+            .collect()
+    }
 
-        let (vote_count, sum_of_squared_votes_all_projects) = {
+    /// Adds a vote to a participant, merging it
+    /// into the payer's existing vote ticket in that mint
+    pub fn record_vote(&mut self, project_key: &Pubkey, payer: Pubkey, mint: Pubkey, amount: u64) -> Result<()> {
+        let participant = self.project_shares
+            .iter_mut()
+            .find(|p| p.project_key == *project_key)
+            .ok_or(ProtocolError::NotInPool)?;
+
+        // A ticket holds one payer's votes in one mint
+        let ticket = participant.share_data.votes
+            .iter_mut()
+            .find(|t| t.payer == payer && t.mint == Some(mint));
+        if let Some(ticket) = ticket {
+            ticket.amount = ticket.amount
+                .checked_add(amount)
+                .ok_or(ProtocolError::AlgorithmFailure)?;
+        } else {
+            participant.share_data.votes.push(VoteTicket::new(payer, Some(mint), amount));
+        }
+
+        Ok(())
+    }
+
+    /// Projects a participant's matching payouts in each mint before and
+    /// after a vote. Both are calculated with the same prices, by the same
+    /// code that records votes and pays out.
+    pub fn simulate_vote(
+        &self,
+        project_key: &Pubkey,
+        payer: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        prices: &BTreeMap<Pubkey, f64>,
+    ) -> Result<(Vec<MintAmount>, Vec<MintAmount>)> {
+        let mut pool = self.clone();
+        pool.calculate_shares(prices)?;
+        let before = pool.calculate_payouts(project_key)?;
+
+        pool.record_vote(project_key, payer, mint, amount)?;
+        pool.calculate_shares(prices)?;
+        let after = pool.calculate_payouts(project_key)?;
+
+        Ok((before, after))
+    }
+
+    /// Runs the Quadratic Funding algorithm over every participant's
+    /// votes, applying the pool's `MatchingCaps`
+    pub fn calculate_shares(&mut self, prices: &BTreeMap<Pubkey, f64>) -> Result<()> {
+        let caps = self.caps.clone();
+        let oracle = self.oracle.clone();
+
+        // Each category runs its own round, so totals are kept per category
+        let (vote_count, sum_of_squared_votes_by_category) = {
             // Block-scope the mutability
 
             // Set up a `BTreeMap` to use to record each project's squared sum of
             // square roots of votes
             let mut vote_count_mut: BTreeMap<Pubkey, f64> = BTreeMap::new();
-            let mut sum_of_squared_votes_all_projects_mut: f64 = 0.0;
+            let mut sum_of_squared_votes_by_category_mut: BTreeMap<Option<u8>, f64> = BTreeMap::new();
 
             // Iterate through all of the projects
             for project in self.project_shares.iter_mut() {
                 // Get the sum of all square roots of each vote
                 let total_square_root_votes_usd: f64 = calculate_total_square_root_votes_usd(
                     &project.share_data.votes,
-                    usdc_usd_price,
+                    prices,
+                    &oracle,
+                    &caps,
                 )?;
 
                 msg!("Sum of square roots of votes: {:?}", total_square_root_votes_usd);
@@ -200,19 +864,50 @@ impl Pool {
 
                 // Add to the vote count `BTreeMap`
                 vote_count_mut.insert(project.project_key, sum_of_roots_squared);
-                sum_of_squared_votes_all_projects_mut += sum_of_roots_squared;
+                *sum_of_squared_votes_by_category_mut
+                    .entry(project.category)
+                    .or_insert(0.0) += sum_of_roots_squared;
             }
 
-            (vote_count_mut, sum_of_squared_votes_all_projects_mut)
+            (vote_count_mut, sum_of_squared_votes_by_category_mut)
         };
 
         // Evaluate each project's distribution from the `vote_count` `HashMap`
-        // and update their distribution amount in the `project_shares`
-        for project in self.project_shares.iter_mut() {
+        let mut shares: Vec<f64> = Vec::with_capacity(self.project_shares.len());
+        for project in self.project_shares.iter() {
+            let sum_of_squared_votes_category = sum_of_squared_votes_by_category
+                .get(&project.category)
+                .copied()
+                .unwrap_or(0.0);
             let updated_share = match vote_count.get(&project.project_key) {
-                Some(vote_count) => vote_count / sum_of_squared_votes_all_projects,
+                Some(_) if sum_of_squared_votes_category == 0.0 => 0.0,
+                Some(vote_count) => vote_count / sum_of_squared_votes_category,
                 None => return Err(ProtocolError::AlgorithmFailure.into()),
             };
+            shares.push(updated_share);
+        }
+
+        // Caps are applied within each category
+        if let Some(max_share) = caps.max_project_share {
+            for category in sum_of_squared_votes_by_category.keys() {
+                let indices: Vec<usize> = self.project_shares
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| p.category == *category)
+                    .map(|(i, _)| i)
+                    .collect();
+
+                let mut category_shares: Vec<f64> = indices.iter().map(|i| shares[*i]).collect();
+                cap_shares(&mut category_shares, max_share);
+
+                for (i, share) in indices.iter().zip(category_shares) {
+                    shares[*i] = share;
+                }
+            }
+        }
+
+        // Update their distribution amount in the `project_shares`
+        for (project, updated_share) in self.project_shares.iter_mut().zip(shares) {
             project.share_data.share = updated_share;
 
             msg!("Updated share for {:?} is {:?}", project.project_key, updated_share);
@@ -227,14 +922,20 @@ pub struct FundingTicket {
     pub source: Pubkey,
     pub mint: Option<Pubkey>,
     pub amount: u64,
+    pub earmark: Earmark,
+    /// Unspent matching already rolled over into another pool,
+    /// for tickets funded by a pool rather than a `FundingSource`
+    pub rolled_over: u64,
 }
 
 impl FundingTicket {
-    pub fn new(source: Pubkey, mint: Option<Pubkey>, amount: u64) -> Self {
+    pub fn new(source: Pubkey, mint: Option<Pubkey>, amount: u64, earmark: Earmark) -> Self {
         Self {
             source,
             mint,
             amount,
+            earmark,
+            rolled_over: 0,
         }
     }
 }
@@ -244,25 +945,91 @@ pub struct Participant {
     pub project_key: Pubkey,
     pub claimed: bool,
     pub share_data: PoolShare,
+    pub category: Option<u8>,
+    /// Set by the project to return votes to donors if the pool is cancelled
+    pub refund_votes: bool,
+    /// Payouts claimed in each mint, before fees
+    pub paid: Vec<MintAmount>,
+    /// Set when the claim deadline passes before the payout is claimed
+    pub forfeited: bool,
 }
 
 // Double check this to make sure it works
 impl Participant {
-    pub fn new(project_key: Pubkey, share_data: PoolShare) -> Self {
+    pub fn new(project_key: Pubkey, share_data: PoolShare, category: Option<u8>) -> Self {
         Self {
             project_key: project_key,
             claimed: false,
             share_data: share_data,
+            category,
+            refund_votes: false,
+            paid: vec![],
+            forfeited: false,
         }
     }
 
-    pub fn new_with_vote(project_key: Pubkey, vote: VoteTicket) -> Self {
+    pub fn new_with_vote(project_key: Pubkey, vote: VoteTicket, category: Option<u8>) -> Self {
         let new_share = PoolShare::new_with_vote(vote);
         Self {
             project_key: project_key,
             claimed: false,
             share_data: new_share,
+            category,
+            refund_votes: false,
+            paid: vec![],
+            forfeited: false,
+        }
+    }
+}
+
+/// An amount held in a single mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, Debug, PartialEq)]
+pub struct MintAmount {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl MintAmount {
+    pub const SPACE: usize = 32 + 8;
+
+    /// The amount recorded for `mint`
+    pub fn amount_in(amounts: &[MintAmount], mint: &Pubkey) -> u64 {
+        amounts
+            .iter()
+            .find(|a| a.mint == *mint)
+            .map_or(0, |a| a.amount)
+    }
+
+    /// Adds to the amount recorded for `mint`
+    pub fn add(amounts: &mut Vec<MintAmount>, mint: Pubkey, amount: u64) -> Result<()> {
+        match amounts.iter_mut().find(|a| a.mint == mint) {
+            Some(entry) => {
+                entry.amount = entry.amount
+                    .checked_add(amount)
+                    .ok_or(ProtocolError::AlgorithmFailure)?;
+            },
+            None => amounts.push(MintAmount { mint, amount }),
         }
+        Ok(())
+    }
+}
+
+/// A track within a pool with its own matching allocation.
+/// Shares are calculated between the participants of a category.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq)]
+pub struct Category {
+    pub name: String,
+    /// Portion of unrestricted funding allocated to this category (0.0 - 1.0)
+    pub allocation: f64,
+}
+
+impl Category {
+    pub const SPACE: usize = 4 + MAX_NAME_LEN + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.name.as_bytes().len() <= MAX_NAME_LEN, ProtocolError::NameTooLong);
+        require!(self.allocation >= 0.0 && self.allocation <= 1.0, ProtocolError::InvalidCategory);
+        Ok(())
     }
 }
 
@@ -305,12 +1072,54 @@ impl VoteTicket {
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// What happens to payouts that aren't claimed
+/// within `window` seconds of the pool being finalized
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct UnclaimedPolicy {
+    pub window: u64,
+    pub fate: UnclaimedFate,
+}
+
+impl UnclaimedPolicy {
+    pub const SPACE: usize = 8 + 1 + 32;
+
+    pub fn validate(&self, pool: &Pubkey) -> Result<()> {
+        require!(self.window >= MIN_CLAIM_WINDOW, ProtocolError::InvalidUnclaimedPolicy);
+        match &self.fate {
+            UnclaimedFate::Rollover(target) => require!(target != pool, ProtocolError::InvalidUnclaimedPolicy),
+            // Only set by a series, for the round it spawns next
+            UnclaimedFate::NextRound => return err!(ProtocolError::InvalidUnclaimedPolicy),
+            _ => {},
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum UnclaimedFate {
+    /// Split among the projects that did claim, within each category
+    Redistribute,
+    /// Moved into another pool's matching as unrestricted funding
+    Rollover(Pubkey),
+    /// Left for the pool's funders to withdraw, pro-rata to what they funded
+    ReturnToFunders,
+    /// Rolled over into the series' next round once it's spawned,
+    /// or returned to funders if claims expire before then
+    NextRound,
+}
+
+/// Lifecycle of a pool:
+/// PendingStart -> Active -> Ended -> Finalized -> Distributed,
+/// with any state before `Finalized` able to move to `Cancelled`.
+/// Variants are serialized by index, so new ones go at the end.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
 pub enum PoolState {
     PendingStart,
     Active,
     Distributed,
-    Closed,
+    Cancelled,
+    Ended,
+    Finalized,
 }
 impl Default for PoolState {
     fn default() -> Self {
@@ -318,6 +1127,95 @@ impl Default for PoolState {
     }
 }
 
+impl PoolState {
+    pub fn can_transition_to(&self, next: &PoolState) -> bool {
+        matches!(
+            (self, next),
+            (PoolState::PendingStart, PoolState::Active)
+                | (PoolState::PendingStart, PoolState::Cancelled)
+                | (PoolState::Active, PoolState::Ended)
+                | (PoolState::Active, PoolState::Cancelled)
+                | (PoolState::Ended, PoolState::Finalized)
+                | (PoolState::Ended, PoolState::Cancelled)
+                | (PoolState::Finalized, PoolState::Distributed)
+        )
+    }
+}
+
+/// Extends a round when late votes move it significantly,
+/// so the outcome can't be swung at the last second.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct SoftClose {
+    /// Seconds before the end of the round the soft close applies
+    pub window: u64,
+    /// Fraction of movement in shares or votes that triggers an extension (0.0 - 1.0)
+    pub threshold: f64,
+    /// Seconds added to the end of the round per extension
+    pub extension: u64,
+    /// Maximum total seconds the round can be extended by
+    pub max_extension: u64,
+}
+
+impl SoftClose {
+    pub const SPACE: usize = 8 + 8 + 8 + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(self.window > 0, ProtocolError::InvalidSoftClose);
+        require!(self.threshold > 0.0 && self.threshold <= 1.0, ProtocolError::InvalidSoftClose);
+        require!(self.extension > 0, ProtocolError::InvalidSoftClose);
+        require!(self.max_extension >= self.extension, ProtocolError::InvalidSoftClose);
+        require!(self.max_extension <= MAX_POOL_DURATION, ProtocolError::InvalidSoftClose);
+        Ok(())
+    }
+}
+
+/// The shares and number of votes in a round at a point in time
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoundActivity {
+    pub shares: Vec<f64>,
+    pub votes: usize,
+}
+
+impl RoundActivity {
+    /// How far the round moved from `before`: the larger of the share
+    /// distribution's total variation and the relative growth in votes.
+    pub fn movement_from(&self, before: &RoundActivity) -> f64 {
+        let share_movement: f64 = self.shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| (share - before.shares.get(i).unwrap_or(&0.0)).abs())
+            .sum::<f64>() / 2.0;
+
+        let vote_movement = self.votes.saturating_sub(before.votes) as f64
+            / before.votes.max(1) as f64;
+
+        share_movement.max(vote_movement)
+    }
+}
+
+/// Lamports paid into a pool's account by a single payer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct RentPayment {
+    pub payer: Pubkey,
+    pub lamports: u64,
+}
+
+impl RentPayment {
+    pub const SPACE: usize = 32 + 8;
+}
+
+/// A recorded change in a pool's state
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct StateTransition {
+    pub from: PoolState,
+    pub to: PoolState,
+    pub timestamp: u64,
+}
+
+impl StateTransition {
+    pub const SPACE: usize = 1 + 1 + 8;
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
 pub enum PoolAccess {
     Open,
@@ -329,59 +1227,119 @@ impl Default for PoolAccess {
     }
 }
 
-fn try_load_price(pyth_account: AccountInfo<'_>) -> Result<f64> {
-    match load_price_feed_from_account_info(&pyth_account) {
-        Ok(price_feed) => {
-            match price_feed.get_price_no_older_than(
-                Clock::get()?.unix_timestamp,
-                60, // No older than 60 seconds ago
-            ) {
-                Some(price) => Ok(price.price as f64),
-                None => Err(ProtocolError::PythPriceFeedPriceFailed.into()),
-            }
+/// Limits applied when calculating shares. Contribution limits are
+/// denominated in USD, and apply to a donor's votes in every mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq)]
+pub struct MatchingCaps {
+    /// Maximum share of the pool any one project can receive (0.0 - 1.0)
+    pub max_project_share: Option<f64>,
+    /// Maximum USD amount counted from a single donor to a single project
+    pub max_donor_contribution: Option<f64>,
+    /// A donor's votes to a project below this USD amount aren't counted
+    pub min_vote_contribution: f64,
+}
+
+impl MatchingCaps {
+    pub const SPACE: usize = (1 + 8) + (1 + 8) + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(max_share) = self.max_project_share {
+            require!(max_share > 0.0 && max_share <= 1.0, ProtocolError::InvalidMatchingCaps);
+        }
+        require!(
+            self.min_vote_contribution.is_finite() && self.min_vote_contribution >= 0.0,
+            ProtocolError::InvalidMatchingCaps
+        );
+        if let Some(max_contribution) = self.max_donor_contribution {
+            require!(max_contribution >= self.min_vote_contribution, ProtocolError::InvalidMatchingCaps);
         }
-        Err(_) => Err(ProtocolError::PythPriceFeedLoadFailed.into()),
+        Ok(())
     }
-}
 
-fn calculate_price_usd(
-    mint: Option<Pubkey>,
-    amount: u64,
-    usdc_usd_price: f64,
-) -> Result<f64> {
-    match mint {
-        Some(mint) => {
-            if mint.eq(&to_pubkey(USDC_MINT)) {
-                Ok(usdc_usd_price * amount as f64)
-            } else if mint.eq(&to_pubkey(USDC_DEVNET_MINT)) {
-                Ok(usdc_usd_price * amount as f64)
-            } else {
-                return Err(ProtocolError::MintNotSupported.into());
-            }
+    /// The USD amount of a donor's votes that counts towards matching
+    pub fn counted_amount(&self, amount: f64) -> f64 {
+        if amount < self.min_vote_contribution {
+            return 0.0;
+        }
+        match self.max_donor_contribution {
+            Some(max_contribution) => amount.min(max_contribution),
+            None => amount,
         }
-        None => return Err(ProtocolError::MintNotSupported.into()),
     }
 }
 
 fn calculate_total_square_root_votes_usd(
     votes: &Vec<VoteTicket>,
-    usdc_usd_price: f64,
+    prices: &BTreeMap<Pubkey, f64>,
+    oracle: &OracleConfig,
+    caps: &MatchingCaps,
 ) -> Result<f64> {
-    let mut total_square_root_votes_usd_mut: f64 = 0.0;
+    // A payer's votes in every mint count as one vote
+    let mut votes_usd: BTreeMap<Pubkey, f64> = BTreeMap::new();
 
     for vote in votes.iter() {
-        let vote_amount_usd =
-            calculate_price_usd(vote.mint, vote.amount, usdc_usd_price)?;
+        // Each vote is valued in its own mint, with that mint's decimals
+        let mint = vote.mint.ok_or(ProtocolError::MintNotSupported)?;
+        let vote_amount_usd = oracle.value_usd(&mint, vote.amount, prices)?;
+        *votes_usd.entry(vote.payer).or_insert(0.0) += vote_amount_usd;
+    }
 
-        // The square root of the vote's USD amount
-        let vote_amount_square_root_usd = vote_amount_usd.sqrt();
+    // The square root of each payer's USD amount, after the caps
+    let total_square_root_votes_usd: f64 = votes_usd
+        .values()
+        .map(|vote_amount_usd| caps.counted_amount(*vote_amount_usd).sqrt())
+        .sum();
 
-        total_square_root_votes_usd_mut += vote_amount_square_root_usd;
-    }
+    Ok(total_square_root_votes_usd)
+}
 
-    Ok(total_square_root_votes_usd_mut as f64)
+/// Checks a pool's schedule starts in the future and
+/// runs between the minimum and maximum durations.
+fn validate_schedule(start: u64, end: u64, now: u64) -> Result<()> {
+    require!(start > now, ProtocolError::PoolInvalidStart);
+    require!(end > start, ProtocolError::PoolInvalidDuration);
+    let duration = end - start;
+    require!(
+        duration >= MIN_POOL_DURATION && duration <= MAX_POOL_DURATION,
+        ProtocolError::PoolInvalidDuration
+    );
+    Ok(())
 }
 
+/// Caps every share at `max_share`, redistributing the excess to the
+/// uncapped shares in proportion to their size. Repeats until no share
+/// is above the cap. Excess that can't be redistributed is left unallocated.
+fn cap_shares(shares: &mut Vec<f64>, max_share: f64) {
+    let mut capped = vec![false; shares.len()];
+
+    loop {
+        let mut excess: f64 = 0.0;
+        for (share, is_capped) in shares.iter_mut().zip(capped.iter_mut()) {
+            if *share > max_share {
+                excess += *share - max_share;
+                *share = max_share;
+                *is_capped = true;
+            }
+        }
+
+        let uncapped_total: f64 = shares
+            .iter()
+            .zip(capped.iter())
+            .filter(|(_, is_capped)| !**is_capped)
+            .map(|(share, _)| share)
+            .sum();
+
+        if excess <= f64::EPSILON || uncapped_total == 0.0 {
+            break;
+        }
+
+        for (share, is_capped) in shares.iter_mut().zip(capped.iter()) {
+            if !*is_capped {
+                *share += excess * *share / uncapped_total;
+            }
+        }
+    }
+}
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub enum UpdatePoolField {
@@ -389,12 +1347,19 @@ pub enum UpdatePoolField {
     AddAdmin(Pubkey),
     RemoveAdmin(Pubkey),
     Approval(PoolAccess),
-    Status(PoolState),
+    Caps(MatchingCaps),
+    AddCategory(Category),
+    Oracle(OracleConfig),
+    SoftClose(Option<SoftClose>),
+    Metadata(String, [u8; 32]),
+    Unclaimed(Option<UnclaimedPolicy>),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::oracle::{MintOracle, OracleSource};
+    use crate::util::to_pubkey;
     use std::collections::BTreeMap;
 
     #[test]
@@ -442,6 +1407,10 @@ mod tests {
                 Participant {
                     project_key: Pubkey::new_unique(),
                     claimed: false,
+                    category: None,
+                    refund_votes: false,
+                    paid: vec![],
+                    forfeited: false,
                     share_data: PoolShare {
                         share: 0.0,
                         votes: vec![
@@ -455,6 +1424,10 @@ mod tests {
                 Participant {
                     project_key: Pubkey::new_unique(),
                     claimed: false,
+                    category: None,
+                    refund_votes: false,
+                    paid: vec![],
+                    forfeited: false,
                     share_data: PoolShare {
                         share: 0.0,
                         votes: vec![
@@ -467,6 +1440,7 @@ mod tests {
             ];
         
         let mut pool = Pool {
+            version: Pool::VERSION,
             pool_id: 12345,
             name: "Sample Pool".to_owned(),
             total_funding: 1000,
@@ -479,6 +1453,16 @@ mod tests {
             pool_state: PoolState::Active,
             pool_access: PoolAccess::Open,
             bump: 255,
+            caps: MatchingCaps::default(),
+            categories: vec![],
+            oracle: OracleConfig::default(),
+            history: vec![],
+            soft_close: None,
+            extended_by: 0,
+            rent_payers: vec![],
+            metadata: Metadata::default(),
+            unclaimed: None,
+            returned: vec![],
         };
 
         fn calculate_total_square_root_votes(
@@ -487,8 +1471,11 @@ mod tests {
             let mut total_square_root_votes_usd_mut: f64 = 0.0;
 
             for vote in votes.iter() {
-                let vote_amount_usd =
-                    calculate_price_usd(vote.mint, vote.amount, 0.99)?;
+                let vote_amount_usd = crate::oracle::calculate_price_usd(
+                    vote.amount,
+                    crate::util::USDC_DECIMALS,
+                    0.99,
+                );
 
                 // The square root of the vote's USD amount
                 let vote_amount_square_root_usd = vote_amount_usd.sqrt();
@@ -579,6 +1566,10 @@ mod tests {
                 Participant {
                     project_key: Pubkey::new_unique(),
                     claimed: false,
+                    category: None,
+                    refund_votes: false,
+                    paid: vec![],
+                    forfeited: false,
                     share_data: PoolShare {
                         share: 0.0,
                         votes: vec![
@@ -592,6 +1583,10 @@ mod tests {
                 Participant {
                     project_key: Pubkey::new_unique(),
                     claimed: false,
+                    category: None,
+                    refund_votes: false,
+                    paid: vec![],
+                    forfeited: false,
                     share_data: PoolShare {
                         share: 0.0,
                         votes: vec![
@@ -604,6 +1599,7 @@ mod tests {
             ];
         
         let pool = Pool {
+            version: Pool::VERSION,
             pool_id: 12345,
             name: "Sample Pool".to_owned(),
             total_funding: 1000,
@@ -616,6 +1612,16 @@ mod tests {
             pool_state: PoolState::Active,
             pool_access: PoolAccess::Open,
             bump: 255,
+            caps: MatchingCaps::default(),
+            categories: vec![],
+            oracle: OracleConfig::default(),
+            history: vec![],
+            soft_close: None,
+            extended_by: 0,
+            rent_payers: vec![],
+            metadata: Metadata::default(),
+            unclaimed: None,
+            returned: vec![],
         };
 
         let mut pool_data = pool.clone();
@@ -623,6 +1629,10 @@ mod tests {
         let participant = Participant {
                 project_key: Pubkey::new_unique(),
                 claimed: false,
+                category: None,
+                refund_votes: false,
+                paid: vec![],
+                forfeited: false,
                 share_data: PoolShare {
                 share: 0.0,
                 votes: vec![]
@@ -638,4 +1648,640 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn try_earmarked_payouts() -> Result<()> {
+        let project_a = Pubkey::new_unique();
+        let project_b = Pubkey::new_unique();
+        let open_source = Pubkey::new_unique();
+        let earmarked_source = Pubkey::new_unique();
+        let (mint, other_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut pool = Pool {
+            project_shares: vec![
+                Participant {
+                    project_key: project_a,
+                    claimed: false,
+                    share_data: PoolShare { share: 0.75, votes: vec![] },
+                    category: None,
+                    refund_votes: false,
+                    paid: vec![],
+                    forfeited: false,
+                },
+                Participant {
+                    project_key: project_b,
+                    claimed: false,
+                    share_data: PoolShare { share: 0.25, votes: vec![] },
+                    category: None,
+                    refund_votes: false,
+                    paid: vec![],
+                    forfeited: false,
+                },
+            ],
+            ..Default::default()
+        };
+
+        pool.record_funding(open_source, Some(mint), 1000, Earmark::Unrestricted)?;
+        pool.record_funding(earmarked_source, Some(mint), 400, Earmark::Participants(vec![project_b]))?;
+
+        assert_eq!(pool.total_funding, 1400);
+        assert_eq!(pool.calculate_payout(&project_a, &mint)?, 750);
+        assert_eq!(pool.calculate_payout(&project_b, &mint)?, 650);
+        assert_eq!(pool.calculate_unspent_for_source(&earmarked_source, &mint), 0);
+
+        // Nobody voted for the earmarked participant
+        pool.project_shares[0].share_data.share = 1.0;
+        pool.project_shares[1].share_data.share = 0.0;
+
+        assert_eq!(pool.calculate_payout(&project_a, &mint)?, 1000);
+        assert_eq!(pool.calculate_unspent_for_source(&earmarked_source, &mint), 400);
+        assert_eq!(pool.calculate_unspent_for_source(&open_source, &mint), 0);
+
+        // Payouts and refunds are kept apart per mint
+        pool.record_funding(earmarked_source, Some(other_mint), 300, Earmark::Participants(vec![project_b]))?;
+        assert_eq!(pool.calculate_payout(&project_a, &other_mint)?, 0);
+        assert_eq!(pool.calculate_unspent_for_source(&earmarked_source, &other_mint), 300);
+        assert_eq!(
+            pool.calculate_payouts(&project_a)?,
+            vec![
+                MintAmount { mint, amount: 1000 },
+                MintAmount { mint: other_mint, amount: 0 },
+            ]
+        );
+
+        assert_eq!(pool.remove_source(&earmarked_source, &mint), 400);
+        assert_eq!(pool.total_funding, 1300);
+        assert_eq!(pool.calculate_unspent_for_source(&earmarked_source, &other_mint), 300);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_matching_caps() -> Result<()> {
+        let mint = Some(to_pubkey("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"));
+        let whale = Pubkey::new_unique();
+
+        let mut pool = Pool {
+            project_shares: vec![
+                Participant::new_with_vote(Pubkey::new_unique(), VoteTicket::new(whale, mint, 10_000_000), None),
+                Participant::new_with_vote(Pubkey::new_unique(), VoteTicket::new(Pubkey::new_unique(), mint, 1_000_000), None),
+                Participant::new_with_vote(Pubkey::new_unique(), VoteTicket::new(Pubkey::new_unique(), mint, 40_000), None),
+            ],
+            caps: MatchingCaps {
+                max_project_share: Some(0.5),
+                max_donor_contribution: Some(4.0),
+                min_vote_contribution: 0.05,
+            },
+            ..Default::default()
+        };
+
+        pool.calculate_shares(&BTreeMap::from([(mint.unwrap(), 1.0)]))?;
+
+        let shares: Vec<f64> = pool.project_shares.iter().map(|p| p.share_data.share).collect();
+
+        // Whale is counted as $4, which puts them over the cap.
+        // The vote below the minimum isn't counted at all.
+        assert!((shares[0] - 0.5).abs() < 1e-9);
+        assert!((shares[1] - 0.5).abs() < 1e-9);
+        assert_eq!(shares[2], 0.0);
+
+        // A donor's votes in every mint are capped together, in USD
+        let other_mint = Pubkey::new_unique();
+        pool.oracle.feeds.push(MintOracle {
+            mint: other_mint,
+            decimals: 9,
+            sources: vec![OracleSource::FixedPeg(1.0)],
+        });
+        let prices = BTreeMap::from([(mint.unwrap(), 1.0), (other_mint, 1.0)]);
+        let (project, donor) = (pool.project_shares[1].project_key, Pubkey::new_unique());
+        pool.caps.max_project_share = None;
+        pool.record_vote(&project, donor, other_mint, 3_000_000_000)?;
+        pool.record_vote(&project, donor, mint.unwrap(), 3_000_000)?;
+        assert_eq!(pool.project_shares[1].share_data.votes.len(), 3);
+        pool.calculate_shares(&prices)?;
+
+        // The donor is counted as $4 rather than $3 in each mint,
+        // for $1 + $4 against the whale's $4
+        let shares: Vec<f64> = pool.project_shares.iter().map(|p| p.share_data.share).collect();
+        let project_votes = (1.0_f64 + 2.0).powi(2);
+        assert!((shares[1] - project_votes / (project_votes + 4.0)).abs() < 1e-9);
+
+        // Nor are votes under the minimum in each mint dropped,
+        // if together they're above it
+        pool.caps.min_vote_contribution = 5.0;
+        pool.caps.max_donor_contribution = None;
+        pool.calculate_shares(&prices)?;
+        assert!((pool.project_shares[1].share_data.share - 6.0 / 16.0).abs() < 1e-9);
+
+        let mut shares = vec![0.7, 0.2, 0.1];
+        cap_shares(&mut shares, 0.4);
+        assert!((shares.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(shares.iter().all(|share| *share <= 0.4 + 1e-9));
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_category_shares() -> Result<()> {
+        let mint = Some(to_pubkey("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"));
+        let infra = Pubkey::new_unique();
+        let education = Pubkey::new_unique();
+        let open_source = Pubkey::new_unique();
+        let earmarked_source = Pubkey::new_unique();
+
+        let mut pool = Pool::default();
+        pool.add_category(Category { name: "Infrastructure".to_owned(), allocation: 0.6 })?;
+        pool.add_category(Category { name: "Education".to_owned(), allocation: 0.4 })?;
+
+        assert!(pool.add_participant(Pubkey::new_unique(), None).is_err());
+        assert!(pool.add_participant(Pubkey::new_unique(), Some(2)).is_err());
+
+        pool.add_participant(infra, Some(0))?;
+        pool.add_participant(education, Some(1))?;
+
+        pool.project_shares[0].share_data.votes.push(VoteTicket::new(Pubkey::new_unique(), mint, 900));
+        pool.project_shares[1].share_data.votes.push(VoteTicket::new(Pubkey::new_unique(), mint, 4));
+
+        pool.calculate_shares(&BTreeMap::from([(mint.unwrap(), 1.0)]))?;
+
+        // Each project is alone in its category
+        assert_eq!(pool.project_shares[0].share_data.share, 1.0);
+        assert_eq!(pool.project_shares[1].share_data.share, 1.0);
+
+        pool.record_funding(open_source, mint, 1000, Earmark::Unrestricted)?;
+        pool.record_funding(earmarked_source, mint, 500, Earmark::Category(1))?;
+
+        assert_eq!(pool.calculate_payout(&infra, &mint.unwrap())?, 600);
+        assert_eq!(pool.calculate_payout(&education, &mint.unwrap())?, 900);
+        assert_eq!(pool.calculate_unspent_for_source(&open_source, &mint.unwrap()), 0);
+
+        assert!(pool.record_funding(open_source, mint, 10, Earmark::Category(3)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_state_transitions() -> Result<()> {
+        let mut pool = Pool {
+            start: 100,
+            end: 200,
+            project_shares: vec![
+                Participant::new(Pubkey::new_unique(), PoolShare::new(), None),
+            ],
+            ..Default::default()
+        };
+
+        // Time-driven states are derived without touching the pool
+        assert_eq!(pool.state_at(50), PoolState::PendingStart);
+        assert_eq!(pool.state_at(150), PoolState::Active);
+        assert_eq!(pool.state_at(250), PoolState::Ended);
+        assert_eq!(pool.pool_state, PoolState::PendingStart);
+
+        // Can't finalize or skip ahead while the round is running
+        assert!(pool.finalize(150).is_err());
+        assert!(pool.transition(PoolState::Distributed, 150).is_err());
+
+        pool.finalize(250)?;
+        assert_eq!(pool.pool_state, PoolState::Finalized);
+        assert_eq!(pool.history.len(), 3);
+        assert_eq!(pool.history[0].timestamp, 100);
+        assert_eq!(pool.history[1].timestamp, 200);
+        assert_eq!(pool.history[2].to, PoolState::Finalized);
+
+        // A finalized round can't be reopened or cancelled
+        assert!(pool.transition(PoolState::Active, 260).is_err());
+        assert!(pool.transition(PoolState::Cancelled, 260).is_err());
+
+        pool.maybe_mark_distributed(260)?;
+        assert_eq!(pool.pool_state, PoolState::Finalized);
+
+        pool.project_shares[0].claimed = true;
+        pool.maybe_mark_distributed(270)?;
+        assert_eq!(pool.pool_state, PoolState::Distributed);
+        assert_eq!(pool.history.len(), 4);
+        assert!(pool.history.len() <= MAX_POOL_TRANSITIONS);
+
+        // Resizes keep room for the transitions that are left
+        assert_eq!(
+            pool.transition_room(),
+            StateTransition::SPACE * (MAX_POOL_TRANSITIONS - 4)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_cancel_and_refund_votes() -> Result<()> {
+        let mint = to_pubkey("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU");
+        let project = Pubkey::new_unique();
+        let donor = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+
+        let mut pool = Pool {
+            start: 100,
+            end: 200,
+            project_shares: vec![
+                Participant::new_with_vote(project, VoteTicket::new(donor, Some(mint), 50), None),
+            ],
+            ..Default::default()
+        };
+        pool.record_funding(source, Some(mint), 1_000, Earmark::Unrestricted)?;
+
+        // Votes can't be refunded until the pool is cancelled
+        assert!(pool.enable_vote_refunds(&project).is_err());
+
+        pool.cancel(150)?;
+        assert_eq!(pool.pool_state, PoolState::Cancelled);
+        assert_eq!(pool.history.last().unwrap().from, PoolState::Active);
+        assert!(pool.cancel(160).is_err());
+
+        // Sources get back exactly what they deposited
+        assert_eq!(pool.remove_source(&source, &mint), 1_000);
+
+        // Donors only get refunds once the project opts in
+        assert!(pool.take_vote_refund(&project, &donor, &mint).is_err());
+        pool.enable_vote_refunds(&project)?;
+        assert_eq!(pool.take_vote_refund(&project, &donor, &mint)?, 50);
+        assert!(pool.take_vote_refund(&project, &donor, &mint).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_reschedule() -> Result<()> {
+        let day = 86400;
+        let mut pool = Pool {
+            start: 10 * day,
+            end: 20 * day,
+            ..Default::default()
+        };
+
+        // Start must be in the future, end after start, within the durations
+        assert!(pool.reschedule(day, 5 * day, 2 * day).is_err());
+        assert!(pool.reschedule(15 * day, 14 * day, day).is_err());
+        assert!(pool.reschedule(15 * day, 15 * day + 60, day).is_err());
+        assert!(pool.reschedule(15 * day, 200 * day, day).is_err());
+
+        pool.reschedule(15 * day, 30 * day, day)?;
+        assert_eq!((pool.start, pool.end), (15 * day, 30 * day));
+
+        // Can't reschedule a running round, or close it before the minimum duration
+        assert!(pool.reschedule(40 * day, 50 * day, 15 * day + 1).is_err());
+        assert!(pool.close_early(15 * day + 60).is_err());
+
+        pool.close_early(20 * day)?;
+        assert_eq!(pool.end, 20 * day);
+        assert_eq!(pool.pool_state, PoolState::Ended);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_soft_close() -> Result<()> {
+        let mint = Some(to_pubkey("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"));
+        let prices = BTreeMap::from([(mint.unwrap(), 1.0)]);
+        let mut pool = Pool {
+            start: 0,
+            end: 10_000,
+            project_shares: vec![
+                Participant::new_with_vote(Pubkey::new_unique(), VoteTicket::new(Pubkey::new_unique(), mint, 100), None),
+                Participant::new_with_vote(Pubkey::new_unique(), VoteTicket::new(Pubkey::new_unique(), mint, 100), None),
+            ],
+            soft_close: Some(SoftClose {
+                window: 600,
+                threshold: 0.1,
+                extension: 300,
+                max_extension: 500,
+            }),
+            ..Default::default()
+        };
+        pool.calculate_shares(&prices)?;
+
+        // A large late vote swings the round and extends it
+        let before = pool.activity();
+        pool.project_shares[0].share_data.votes.push(VoteTicket::new(Pubkey::new_unique(), mint, 900));
+        pool.calculate_shares(&prices)?;
+        assert_eq!(pool.apply_soft_close(&before, 5_000), None);
+        assert_eq!(pool.apply_soft_close(&before, 9_700), Some(10_300));
+
+        // Extensions stop at the cap
+        assert_eq!(pool.apply_soft_close(&before, 10_100), Some(10_500));
+        assert_eq!(pool.apply_soft_close(&before, 10_400), None);
+        assert_eq!(pool.extended_by, 500);
+
+        // Small movements don't extend the round
+        let mut quiet = pool.clone();
+        quiet.extended_by = 0;
+        assert_eq!(quiet.apply_soft_close(&quiet.activity(), 10_400), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_rent_and_settlement() -> Result<()> {
+        let mint = Some(to_pubkey("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"));
+        let creator = Pubkey::new_unique();
+        let voter = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+
+        let mut pool = Pool::default();
+        pool.record_rent(creator, 1_000);
+        pool.record_rent(voter, 0);
+        pool.record_rent(voter, 50);
+        pool.record_rent(voter, 25);
+        assert_eq!(pool.rent_payers, vec![
+            RentPayment { payer: creator, lamports: 1_000 },
+            RentPayment { payer: voter, lamports: 75 },
+        ]);
+
+        // Sources with funds in an open pool aren't settled
+        pool.record_funding(source, mint, 500, Earmark::Unrestricted)?;
+        assert!(!pool.is_source_settled(&source, &[]));
+        assert!(pool.can_close().is_err());
+
+        // Once distributed, a source is settled when its
+        // unspent matching is refunded in every mint
+        let other_mint = Pubkey::new_unique();
+        let mut distributed = pool.clone();
+        distributed.record_funding(source, Some(other_mint), 200, Earmark::Unrestricted)?;
+        distributed.pool_state = PoolState::Distributed;
+        let refunded = vec![MintAmount { mint: mint.unwrap(), amount: 500 }];
+        assert!(!distributed.is_source_settled(&source, &refunded));
+        let refunded = vec![refunded[0].clone(), MintAmount { mint: other_mint, amount: 200 }];
+        assert!(distributed.is_source_settled(&source, &refunded));
+
+        // Once cancelled, a source is settled after it's refunded
+        pool.pool_state = PoolState::Cancelled;
+        assert!(!pool.is_source_settled(&source, &[]));
+        assert!(pool.can_close().is_err());
+        pool.remove_source(&source, &mint.unwrap());
+        assert!(pool.is_source_settled(&source, &[]));
+        assert!(pool.can_close().is_ok());
+
+        // Votes a project has opened up for refunds must be returned first
+        let project = Pubkey::new_unique();
+        let mut participant = Participant::new(project, PoolShare::new(), None);
+        participant.share_data.votes.push(VoteTicket::new(voter, mint, 100));
+        pool.project_shares.push(participant);
+        assert!(pool.can_close().is_ok());
+        pool.enable_vote_refunds(&project)?;
+        assert!(pool.can_close().is_err());
+        pool.take_vote_refund(&project, &voter, &mint.unwrap())?;
+        assert!(pool.can_close().is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_simulate_vote() -> Result<()> {
+        let mint = to_pubkey(crate::util::USDC_MINT);
+        let prices = BTreeMap::from([(mint, 1.0)]);
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let donor = Pubkey::new_unique();
+
+        let mut pool = Pool {
+            version: Pool::VERSION,
+            pool_id: 1,
+            name: "Simulated".to_owned(),
+            total_funding: 10_000,
+            balance: 10_000,
+            start: 0,
+            end: 0,
+            admins: vec![],
+            project_shares: vec![
+                Participant::new(a, PoolShare::new(), None),
+                Participant::new(b, PoolShare::new(), None),
+            ],
+            funders: vec![FundingTicket::new(Pubkey::new_unique(), Some(mint), 10_000, Earmark::Unrestricted)],
+            pool_state: PoolState::Active,
+            pool_access: PoolAccess::Open,
+            bump: 255,
+            caps: MatchingCaps::default(),
+            categories: vec![],
+            oracle: OracleConfig::default(),
+            history: vec![],
+            soft_close: None,
+            extended_by: 0,
+            rent_payers: vec![],
+            metadata: Metadata::default(),
+            unclaimed: None,
+            returned: vec![],
+        };
+        pool.record_vote(&a, Pubkey::new_unique(), mint, 100)?;
+        pool.record_vote(&b, Pubkey::new_unique(), mint, 100)?;
+        pool.calculate_shares(&prices)?;
+        assert_eq!(pool.calculate_payout(&a, &mint)?, 5_000);
+
+        // A new donor moves more of the match than a repeat donor
+        let (before, after) = pool.simulate_vote(&a, donor, mint, 100, &prices)?;
+        assert_eq!(before, vec![MintAmount { mint, amount: 5_000 }]);
+        assert_eq!(after, vec![MintAmount { mint, amount: 8_000 }]);
+
+        pool.record_vote(&a, donor, mint, 100)?;
+        let (_, repeat) = pool.simulate_vote(&a, donor, mint, 100, &prices)?;
+        let repeat = repeat[0].amount;
+        pool.calculate_shares(&prices)?;
+        assert!(repeat > pool.calculate_payout(&a, &mint)?);
+        assert!(repeat - pool.calculate_payout(&a, &mint)? < after[0].amount - before[0].amount);
+
+        // Simulating doesn't change the pool
+        assert_eq!(pool.project_shares[0].share_data.votes.len(), 2);
+
+        // Votes in another mint get their own ticket, but
+        // still count as the same payer's vote
+        let other_mint = Pubkey::new_unique();
+        pool.oracle.feeds.push(MintOracle {
+            mint: other_mint,
+            decimals: 6,
+            sources: vec![OracleSource::FixedPeg(1.0)],
+        });
+        let prices = BTreeMap::from([(mint, 1.0), (other_mint, 1.0)]);
+        let (_, split) = pool.simulate_vote(&a, donor, other_mint, 100, &prices)?;
+        assert_eq!(split[0].amount, repeat);
+        pool.record_vote(&a, donor, other_mint, 100)?;
+        let votes = &pool.project_shares[0].share_data.votes;
+        assert_eq!(votes.len(), 3);
+        assert_eq!(votes[1].mint, Some(mint));
+        assert_eq!(votes[2].mint, Some(other_mint));
+
+        assert!(pool.simulate_vote(&Pubkey::new_unique(), donor, mint, 100, &prices).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_claim() -> Result<()> {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (mint, other_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut pool = Pool {
+            project_shares: vec![
+                Participant::new(a, PoolShare { share: 0.75, votes: vec![] }, None),
+                Participant::new(b, PoolShare { share: 0.25, votes: vec![] }, None),
+            ],
+            funders: vec![
+                FundingTicket::new(Pubkey::new_unique(), Some(mint), 1000, Earmark::Unrestricted),
+                FundingTicket::new(Pubkey::new_unique(), Some(other_mint), 100, Earmark::Unrestricted),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(pool.claim(&a, &mint, 0)?, 750);
+        assert!(pool.claim(&a, &mint, 0).is_err());
+        assert!(pool.claim(&Pubkey::new_unique(), &mint, 0).is_err());
+        assert!(!pool.project_shares[1].claimed);
+        assert_eq!(pool.claim(&b, &mint, 0)?, 250);
+
+        // Each mint is paid out separately
+        assert!(!pool.is_settled(&pool.project_shares[0]));
+        assert_eq!(pool.claim(&a, &other_mint, 0)?, 75);
+        assert!(pool.claim(&a, &other_mint, 0).is_err());
+        assert!(pool.is_settled(&pool.project_shares[0]));
+        assert_eq!(pool.project_shares[0].paid, vec![
+            MintAmount { mint, amount: 750 },
+            MintAmount { mint: other_mint, amount: 75 },
+        ]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_expire_claims() -> Result<()> {
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let source = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let finalized_at = 1_000;
+        let deadline = finalized_at + MIN_CLAIM_WINDOW;
+
+        let new_pool = |fate: UnclaimedFate| Pool {
+            project_shares: vec![
+                Participant::new(a, PoolShare { share: 0.25, votes: vec![] }, None),
+                Participant::new(b, PoolShare { share: 0.25, votes: vec![] }, None),
+                Participant::new(c, PoolShare { share: 0.5, votes: vec![] }, None),
+            ],
+            funders: vec![FundingTicket::new(source, Some(mint), 1000, Earmark::Unrestricted)],
+            pool_state: PoolState::Finalized,
+            history: vec![StateTransition {
+                from: PoolState::Ended,
+                to: PoolState::Finalized,
+                timestamp: finalized_at,
+            }],
+            unclaimed: Some(UnclaimedPolicy { window: MIN_CLAIM_WINDOW, fate }),
+            ..Default::default()
+        };
+
+        // Redistributed: `a` claimed early, `b` claimed at the
+        // deadline, and `c`'s payout is split between them
+        let mut pool = new_pool(UnclaimedFate::Redistribute);
+        assert_eq!(pool.claim_deadline(), Some(deadline));
+        assert_eq!(pool.claim(&a, &mint, finalized_at)?, 250);
+        assert_eq!(pool.claim(&b, &mint, deadline)?, 250);
+        assert!(pool.claim(&c, &mint, deadline + 1).is_err());
+        assert!(pool.expire_claims(deadline).is_err());
+
+        assert_eq!(pool.expire_claims(deadline + 1)?, vec![MintAmount { mint, amount: 500 }]);
+        assert_eq!(pool.take_forfeited(&mint)?, 0);
+        assert!(pool.claim(&c, &mint, deadline + 1).is_err());
+        assert_eq!(pool.claim(&a, &mint, deadline + 1)?, 250);
+        pool.maybe_mark_distributed(deadline + 1)?;
+        assert_eq!(pool.pool_state, PoolState::Finalized);
+        assert_eq!(pool.claim(&b, &mint, deadline + 1)?, 250);
+        pool.maybe_mark_distributed(deadline + 1)?;
+        assert_eq!(pool.pool_state, PoolState::Distributed);
+        assert_eq!(pool.calculate_unspent_for_source(&source, &mint), 0);
+
+        // Returned to funders
+        let mut pool = new_pool(UnclaimedFate::ReturnToFunders);
+        pool.claim(&a, &mint, finalized_at)?;
+        assert_eq!(pool.expire_claims(deadline + 1)?, vec![MintAmount { mint, amount: 750 }]);
+        assert!(pool.claim(&a, &mint, deadline + 1).is_err());
+        pool.maybe_mark_distributed(deadline + 1)?;
+        assert_eq!(pool.pool_state, PoolState::Distributed);
+        assert_eq!(pool.calculate_unspent_for_source(&source, &mint), 750);
+
+        // Rolled over, so nothing is left for the funders here.
+        // The pool is only distributed once it's been rolled over,
+        // and it can only be rolled over once.
+        let mut pool = new_pool(UnclaimedFate::Rollover(Pubkey::new_unique()));
+        pool.claim(&a, &mint, finalized_at)?;
+        assert_eq!(pool.expire_claims(deadline + 1)?, vec![MintAmount { mint, amount: 750 }]);
+        pool.maybe_mark_distributed(deadline + 1)?;
+        assert_eq!(pool.pool_state, PoolState::Finalized);
+        assert_eq!(pool.take_forfeited(&mint)?, 750);
+        assert_eq!(pool.take_forfeited(&mint)?, 0);
+        pool.maybe_mark_distributed(deadline + 1)?;
+        assert_eq!(pool.pool_state, PoolState::Distributed);
+        assert_eq!(pool.calculate_unspent_for_source(&source, &mint), 0);
+
+        // Once rolled over, the fate can't fall back to the funders
+        pool.return_unclaimed_to_funders();
+        assert!(matches!(pool.unclaimed.as_ref().unwrap().fate, UnclaimedFate::Rollover(_)));
+
+        // When the next pool can't be funded, the funders get it instead
+        let mut pool = new_pool(UnclaimedFate::Rollover(Pubkey::new_unique()));
+        pool.claim(&a, &mint, finalized_at)?;
+        pool.expire_claims(deadline + 1)?;
+        pool.return_unclaimed_to_funders();
+        assert_eq!(pool.unclaimed.as_ref().unwrap().fate, UnclaimedFate::ReturnToFunders);
+        assert_eq!(pool.take_forfeited(&mint)?, 0);
+        pool.maybe_mark_distributed(deadline + 1)?;
+        assert_eq!(pool.pool_state, PoolState::Distributed);
+        assert_eq!(pool.calculate_unspent_for_source(&source, &mint), 750);
+
+        // Matching returned by the next pool is unspent for the funders
+        pool.record_returned(mint, 100)?;
+        assert_eq!(pool.calculate_unspent_for_source(&source, &mint), 850);
+
+        // Without a next round to roll into, funders get it back
+        let mut pool = new_pool(UnclaimedFate::NextRound);
+        pool.expire_claims(deadline + 1)?;
+        assert_eq!(pool.unclaimed.as_ref().unwrap().fate, UnclaimedFate::ReturnToFunders);
+        assert_eq!(pool.calculate_unspent_for_source(&source, &mint), 1000);
+
+        // Pools without a policy never expire
+        let mut pool = new_pool(UnclaimedFate::Redistribute);
+        pool.unclaimed = None;
+        assert!(pool.expire_claims(u64::MAX).is_err());
+        assert_eq!(pool.claim(&c, &mint, u64::MAX)?, 500);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_roll_over_leftovers() -> Result<()> {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let earlier_round = Pubkey::new_unique();
+        let next_round = Pubkey::new_unique();
+        let (mint, other_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut pool = Pool {
+            project_shares: vec![
+                Participant::new(a, PoolShare { share: 0.5, votes: vec![] }, None),
+                Participant::new(b, PoolShare { share: 0.5, votes: vec![] }, None),
+            ],
+            funders: vec![
+                FundingTicket::new(earlier_round, Some(mint), 1000, Earmark::Unrestricted),
+                FundingTicket::new(earlier_round, Some(other_mint), 100, Earmark::Unrestricted),
+            ],
+            pool_state: PoolState::Finalized,
+            unclaimed: Some(UnclaimedPolicy { window: MIN_CLAIM_WINDOW, fate: UnclaimedFate::ReturnToFunders }),
+            ..Default::default()
+        };
+
+        pool.project_shares[1].share_data.share = 0.25;
+        // Rolled in matching is only taken once, in each mint
+        assert_eq!(pool.take_rolled_in_unspent(&earlier_round, &mint), 250);
+        assert_eq!(pool.take_rolled_in_unspent(&earlier_round, &mint), 0);
+        assert_eq!(pool.take_rolled_in_unspent(&earlier_round, &other_mint), 25);
+
+        // Only payouts waiting for the next round follow it
+        pool.roll_unclaimed_into(next_round);
+        assert_eq!(pool.unclaimed.as_ref().unwrap().fate, UnclaimedFate::ReturnToFunders);
+        pool.unclaimed.as_mut().unwrap().fate = UnclaimedFate::NextRound;
+        assert!(pool.unclaimed.as_ref().unwrap().validate(&Pubkey::new_unique()).is_err());
+        pool.roll_unclaimed_into(next_round);
+        assert_eq!(pool.unclaimed.as_ref().unwrap().fate, UnclaimedFate::Rollover(next_round));
+        pool.roll_unclaimed_into(Pubkey::new_unique());
+        assert_eq!(pool.unclaimed.as_ref().unwrap().fate, UnclaimedFate::Rollover(next_round));
+
+        Ok(())
+    }
+}
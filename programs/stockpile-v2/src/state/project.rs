@@ -1,44 +1,71 @@
 use anchor_lang::prelude::*;
+use std::collections::BTreeMap;
 
-use crate::{error::ProtocolError, util::MAX_NAME_LEN};
+use crate::{
+    error::ProtocolError,
+    oracle::{to_ui_amount, OracleConfig},
+    state::{metadata::Metadata, pool::PoolState},
+    util::{MAX_NAME_LEN, MAX_PROJECT_MINTS, USD_DECIMALS},
+};
 
 #[account]
 #[derive(Default)]
 pub struct Project {
+    pub version: u8,
     pub project_id: u64,
     pub name: String,
-    pub raised: u64, // Denominated in USDC
-    pub goal: u64, // Denominated in USDC
-    pub balance: u64, // Denominated in USDC
+    pub raised: u64, // USD value of `balances`, 6 decimals
+    pub goal: u64, // Denominated in USD, 6 decimals
+    pub balance: u64, // USD value of `balances`, 6 decimals
     pub contributors: u8,
     pub admins: Vec<Pubkey>,
     pub beneficiary: Pubkey,
     pub bump: u8,
     pub status: ProjectStatus,
+    pub campaign: Option<Campaign>,
+    pub balances: Vec<MintBalance>,
+    /// Receives the account's rent when it's closed
+    pub rent_payer: Pubkey,
+    pub metadata: Metadata,
+    /// Whether an all-or-nothing campaign met its goal,
+    /// settled once its deadline has passed
+    pub goal_met: Option<bool>,
 }
 
 impl Project {
     pub const SEED_PREFIX: &'static str = "fundraiser";
 
-    pub const SPACE: usize = 8 
-        + 4                         // u64
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 8                         // u64
         + 4 + MAX_NAME_LEN          // String
-        + 4                         // u64
-        + 4                         // u64
-        + 4                         // u64
+        + 8                         // u64
+        + 8                         // u64
+        + 8                         // u64
         + 1                         // u8
-        + 160                       // Vec<Pubkey> (Max 5)
+        + 4 + (32 * 5)              // Vec<Pubkey> (Initial Alloc. for 5)
         + 32                        // Pubkey
         + 1                         // u8
-        + 4                         // Enum (Singleton)
-        + 250;                      // Padding
+        + 1                         // Enum (Singleton)
+        + 1 + Campaign::SPACE       // Option<Campaign>
+        + 4 + (MintBalance::SPACE * MAX_PROJECT_MINTS) // Vec<MintBalance>
+        + 32                        // Pubkey
+        + Metadata::SPACE           // Metadata
+        + 1 + 1                     // Option<bool>
+        + 250;                      // Room for additional admins
 
-    pub fn new(project_id: u64, name: String, admins: Vec<Pubkey>, goal: u64, beneficiary: Pubkey, bump: u8) -> Result<Self> {
+    pub fn new(project_id: u64, name: String, admins: Vec<Pubkey>, goal: u64, beneficiary: Pubkey, campaign: Option<Campaign>, rent_payer: Pubkey, bump: u8) -> Result<Self> {
         let initial: u64 = 0;
         if name.as_bytes().len() > MAX_NAME_LEN {
             return Err(ProtocolError::NameTooLong.into());
         }
+        if let Some(campaign) = &campaign {
+            campaign.validate()?;
+        }
         Ok(Self {
+            version: Self::VERSION,
             project_id,
             name,
             raised: initial,
@@ -48,6 +75,8 @@ impl Project {
             admins,
             beneficiary,
             bump,
+            campaign,
+            rent_payer,
             ..Default::default()
         })
     }
@@ -59,6 +88,211 @@ impl Project {
             ProjectStatus::Closed => err!(ProtocolError::ClosedProject),
         }
     }
+
+    pub fn balance_of(&self, mint: &Pubkey) -> u64 {
+        self.balances
+            .iter()
+            .find(|b| b.mint == *mint)
+            .map(|b| b.balance)
+            .unwrap_or(0)
+    }
+
+    /// Credits funds received in a mint
+    pub fn deposit(&mut self, mint: Pubkey, decimals: u8, amount: u64) -> Result<()> {
+        let bucket = match self.balances.iter_mut().position(|b| b.mint == mint) {
+            Some(index) => &mut self.balances[index],
+            None => {
+                require!(self.balances.len() < MAX_PROJECT_MINTS, ProtocolError::MintNotSupported);
+                self.balances.push(MintBalance::new(mint, decimals));
+                self.balances.last_mut().unwrap()
+            }
+        };
+
+        bucket.raised = bucket.raised.checked_add(amount).ok_or(ProtocolError::InsufficientBalance)?;
+        bucket.balance = bucket.balance.checked_add(amount).ok_or(ProtocolError::InsufficientBalance)?;
+
+        Ok(())
+    }
+
+    /// Debits funds sent out of the project in a mint
+    pub fn withdraw(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        let bucket = self.balances
+            .iter_mut()
+            .find(|b| b.mint == *mint)
+            .ok_or(ProtocolError::InsufficientBalance)?;
+
+        bucket.balance = bucket.balance.checked_sub(amount).ok_or(ProtocolError::InsufficientBalance)?;
+
+        Ok(())
+    }
+
+    /// Reverses a contribution, debiting both the amount raised and the balance
+    pub fn refund(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        self.withdraw(mint, amount)?;
+
+        if let Some(bucket) = self.balances.iter_mut().find(|b| b.mint == *mint) {
+            bucket.raised = bucket.raised.saturating_sub(amount);
+        }
+
+        Ok(())
+    }
+
+    /// Recalculates the USD value of `raised` and `balance` from every mint
+    pub fn revalue(&mut self, prices: &BTreeMap<Pubkey, f64>) -> Result<()> {
+        let mut raised_usd: f64 = 0.0;
+        let mut balance_usd: f64 = 0.0;
+
+        for bucket in &self.balances {
+            let price = prices
+                .get(&bucket.mint)
+                .ok_or(ProtocolError::OraclePriceUnavailable)?;
+            raised_usd += to_ui_amount(bucket.raised, bucket.decimals) * price;
+            balance_usd += to_ui_amount(bucket.balance, bucket.decimals) * price;
+        }
+
+        let usd_scale = 10_f64.powi(USD_DECIMALS as i32);
+        self.raised = (raised_usd * usd_scale) as u64;
+        self.balance = (balance_usd * usd_scale) as u64;
+
+        Ok(())
+    }
+
+    /// Loads prices for every mint the project holds and revalues it
+    pub fn try_refresh_value(&mut self, oracle: &OracleConfig, oracle_accounts: &[AccountInfo<'_>]) -> Result<()> {
+        let mints: Vec<Pubkey> = self.balances.iter().map(|b| b.mint).collect();
+        let prices = oracle.load_prices(&mints, oracle_accounts)?;
+        self.revalue(&prices)
+    }
+
+    /// Like `try_refresh_value`, but an unavailable oracle leaves the
+    /// USD value stale instead of failing, so donors are never blocked
+    pub fn refresh_value(&mut self, oracle: &OracleConfig, oracle_accounts: &[AccountInfo<'_>]) {
+        if let Err(e) = self.try_refresh_value(oracle, oracle_accounts) {
+            msg!("Project value not refreshed: {:?}", e);
+        }
+    }
+
+    /// Checks the project is active, and its campaign hasn't ended
+    pub fn can_contribute(&self) -> Result<()> {
+        self.is_active()?;
+
+        if let Some(campaign) = &self.campaign {
+            let current_time = Clock::get()?.unix_timestamp as u64;
+            require!(current_time <= campaign.deadline, ProtocolError::CampaignEnded);
+        }
+
+        Ok(())
+    }
+
+    /// Whether anything has been raised, in any mint
+    pub fn has_raised(&self) -> bool {
+        self.raised > 0 || self.balances.iter().any(|b| b.raised > 0)
+    }
+
+    /// Settles whether an all-or-nothing campaign met its goal. It's
+    /// valued once, the first time it's checked after the deadline, so
+    /// later price moves can't flip it between withdrawable and refundable.
+    pub fn settle_campaign(&mut self, now: u64, oracle: &OracleConfig, oracle_accounts: &[AccountInfo<'_>]) -> Result<()> {
+        let ended = match &self.campaign {
+            Some(campaign) if campaign.all_or_nothing => now > campaign.deadline,
+            _ => false,
+        };
+        if ended && self.goal_met.is_none() {
+            self.try_refresh_value(oracle, oracle_accounts)?;
+            self.goal_met = Some(self.raised >= self.goal);
+        }
+        Ok(())
+    }
+
+    /// All-or-nothing campaigns can only be withdrawn from once
+    /// the deadline has passed and they've settled with the goal met.
+    pub fn can_withdraw(&self, now: u64) -> Result<()> {
+        match &self.campaign {
+            Some(campaign) if campaign.all_or_nothing => {
+                require!(now > campaign.deadline, ProtocolError::CampaignStillActive);
+                require!(self.goal_met == Some(true), ProtocolError::GoalNotMet);
+                Ok(())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Contributions to an all-or-nothing campaign are refundable
+    /// once the deadline passes and it's settled without meeting the goal.
+    pub fn can_refund(&self, now: u64) -> Result<()> {
+        match &self.campaign {
+            Some(campaign) if campaign.all_or_nothing => {
+                require!(now > campaign.deadline, ProtocolError::CampaignStillActive);
+                require!(self.goal_met == Some(false), ProtocolError::GoalMet);
+                Ok(())
+            },
+            _ => err!(ProtocolError::NotRefundable),
+        }
+    }
+
+    /// A project can only be closed once it's paid out everything it
+    /// holds, and every pool it entered has paid out or been cancelled
+    pub fn can_close(&self, pool_states: &[PoolState]) -> Result<()> {
+        require!(
+            self.balances.iter().all(|b| b.balance == 0),
+            ProtocolError::AccountHoldsFunds
+        );
+        require!(
+            pool_states
+                .iter()
+                .all(|s| matches!(s, PoolState::Distributed | PoolState::Cancelled)),
+            ProtocolError::ProjectPoolsUnsettled
+        );
+        Ok(())
+    }
+
+    /// Funds in an all-or-nothing campaign must stay refundable,
+    /// so these projects can't take votes or matching from pools.
+    pub fn can_join_pool(&self) -> Result<()> {
+        self.is_active()?;
+
+        match &self.campaign {
+            Some(campaign) if campaign.all_or_nothing => err!(ProtocolError::AllOrNothingCampaign),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Amounts raised and held by a project in a single mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Default)]
+pub struct MintBalance {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub raised: u64,
+    pub balance: u64,
+}
+
+impl MintBalance {
+    pub const SPACE: usize = 32 + 1 + 8 + 8;
+
+    pub fn new(mint: Pubkey, decimals: u8) -> Self {
+        Self {
+            mint,
+            decimals,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct Campaign {
+    pub deadline: u64,
+    pub all_or_nothing: bool,
+}
+
+impl Campaign {
+    pub const SPACE: usize = 8 + 1;
+
+    pub fn validate(&self) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp as u64;
+        require!(self.deadline > current_time, ProtocolError::CampaignEnded);
+        Ok(())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
@@ -81,4 +315,83 @@ pub enum UpdateField {
     RemoveAdmin(Pubkey),
     Beneficiary(Pubkey),
     Status(ProjectStatus),
+    Campaign(Option<Campaign>),
+    Metadata(String, [u8; 32]),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_mint_balances() -> Result<()> {
+        let usdc = Pubkey::new_unique();
+        let bonk = Pubkey::new_unique();
+
+        let mut project = Project::default();
+
+        project.deposit(usdc, 6, 2_000_000)?;
+        project.deposit(bonk, 5, 300_000_000)?;
+        project.deposit(usdc, 6, 500_000)?;
+        project.withdraw(&usdc, 1_000_000)?;
+
+        assert!(project.withdraw(&bonk, 300_000_001).is_err());
+        assert_eq!(project.balance_of(&usdc), 1_500_000);
+        assert_eq!(project.balances[0].raised, 2_500_000);
+
+        let prices = BTreeMap::from([(usdc, 1.0), (bonk, 0.00002)]);
+        project.revalue(&prices)?;
+
+        // 2.5 USDC + 3000 BONK at $0.00002
+        assert_eq!(project.raised, 2_560_000);
+        assert_eq!(project.balance, 1_560_000);
+
+        project.refund(&bonk, 300_000_000)?;
+        project.revalue(&prices)?;
+        assert_eq!(project.raised, 2_500_000);
+
+        // Funds and unsettled pools keep the project open
+        assert!(project.can_close(&[]).is_err());
+        project.withdraw(&usdc, 1_500_000)?;
+        project.can_close(&[PoolState::Distributed, PoolState::Cancelled])?;
+        assert!(project.can_close(&[PoolState::Distributed, PoolState::Ended]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_settle_campaign() -> Result<()> {
+        let oracle = OracleConfig::default();
+        let mut project = Project {
+            goal: 1_000_000,
+            campaign: Some(Campaign { deadline: 100, all_or_nothing: true }),
+            ..Default::default()
+        };
+
+        // Nothing is settled before the deadline
+        project.settle_campaign(100, &oracle, &[])?;
+        assert_eq!(project.goal_met, None);
+        assert!(project.can_refund(100).is_err());
+        assert!(project.can_withdraw(100).is_err());
+
+        project.settle_campaign(101, &oracle, &[])?;
+        assert_eq!(project.goal_met, Some(false));
+        assert!(project.can_refund(101).is_ok());
+        assert!(project.can_withdraw(101).is_err());
+
+        // Later valuations don't change the outcome
+        project.raised = 2_000_000;
+        project.settle_campaign(200, &oracle, &[])?;
+        assert_eq!(project.goal_met, Some(false));
+        assert!(project.can_refund(200).is_ok());
+
+        // Projects without an all-or-nothing campaign are never settled
+        let mut project = Project::default();
+        project.settle_campaign(u64::MAX, &oracle, &[])?;
+        assert_eq!(project.goal_met, None);
+        assert!(project.can_withdraw(u64::MAX).is_ok());
+        assert!(project.can_refund(u64::MAX).is_err());
+
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+/// Records how much a donor has contributed directly to a
+/// project in a given mint, so the contribution can be refunded.
+#[account]
+#[derive(Default)]
+pub struct ContributionReceipt {
+    pub version: u8,
+    pub project: Pubkey,
+    pub donor: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// Fees paid on top of `amount`
+    pub fees: u64,
+    pub bump: u8,
+}
+
+impl ContributionReceipt {
+    pub const SEED_PREFIX: &'static str = "receipt";
+
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 32                        // Pubkey
+        + 32                        // Pubkey
+        + 32                        // Pubkey
+        + 8                         // u64
+        + 8                         // u64
+        + 1                         // u8
+        + 32;                       // Padding
+}
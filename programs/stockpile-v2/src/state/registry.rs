@@ -0,0 +1,142 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::ProtocolError, util::MAX_DIRECTORY_PAGE_LEN};
+
+/// A project's participation in a pool. The pool's `project_shares`
+/// index its entries, and a project's `ProjectPools` index the pools
+/// it's entered, so both can be listed without scanning the program.
+#[account]
+#[derive(Default)]
+pub struct PoolEntry {
+    pub version: u8,
+    pub pool: Pubkey,
+    pub project: Pubkey,
+    pub category: Option<u8>,
+    pub joined_at: u64,
+    /// Share of the matching, set when the payout is claimed
+    pub share: f64,
+    /// Payout claimed by the project, after fees
+    pub claimed: u64,
+    pub bump: u8,
+}
+
+impl PoolEntry {
+    pub const SEED_PREFIX: &'static str = "entry";
+
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 32                        // Pubkey
+        + 32                        // Pubkey
+        + 1 + 1                     // Option<u8>
+        + 8                         // u64
+        + 8                         // f64
+        + 8                         // u64
+        + 1;                        // u8
+
+    pub fn new(pool: Pubkey, project: Pubkey, category: Option<u8>, joined_at: u64, bump: u8) -> Self {
+        Self {
+            version: Self::VERSION,
+            pool,
+            project,
+            category,
+            joined_at,
+            share: 0.0,
+            claimed: 0,
+            bump,
+        }
+    }
+}
+
+/// Pools a project has entered, in the order it entered them
+#[account]
+#[derive(Default)]
+pub struct ProjectPools {
+    pub version: u8,
+    pub project: Pubkey,
+    pub pools: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl ProjectPools {
+    pub const SEED_PREFIX: &'static str = "project_pools";
+
+    pub const VERSION: u8 = 1;
+
+    /// Initial size, the account grows as pools are added
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 32                        // Pubkey
+        + 4 + (32 * 4)              // Vec<Pubkey>
+        + 1;                        // u8
+
+    pub fn new(project: Pubkey, bump: u8) -> Self {
+        Self {
+            version: Self::VERSION,
+            project,
+            pools: vec![],
+            bump,
+        }
+    }
+}
+
+/// A page of the directory of active pools. New pools are added
+/// to the latest page, and pools that have ended or closed are
+/// pruned, so pages can be sparse.
+#[account]
+#[derive(Default)]
+pub struct PoolDirectory {
+    pub version: u8,
+    pub page: u32,
+    pub pools: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl PoolDirectory {
+    pub const SEED_PREFIX: &'static str = "directory";
+
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 4                         // u32
+        + 4 + (32 * MAX_DIRECTORY_PAGE_LEN) // Vec<Pubkey>
+        + 1;                        // u8
+
+    pub fn new(page: u32, bump: u8) -> Self {
+        Self {
+            version: Self::VERSION,
+            page,
+            pools: vec![],
+            bump,
+        }
+    }
+
+    /// Adds a pool, returning whether the page is now full
+    pub fn add(&mut self, pool: Pubkey) -> Result<bool> {
+        require!(self.pools.len() < MAX_DIRECTORY_PAGE_LEN, ProtocolError::DirectoryPageFull);
+        self.pools.push(pool);
+        Ok(self.pools.len() == MAX_DIRECTORY_PAGE_LEN)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_directory_pages() -> Result<()> {
+        let mut page = PoolDirectory::new(0, 255);
+        for _ in 0..MAX_DIRECTORY_PAGE_LEN - 1 {
+            assert!(!page.add(Pubkey::new_unique())?);
+        }
+        assert!(page.add(Pubkey::new_unique())?);
+        assert!(page.add(Pubkey::new_unique()).is_err());
+
+        // A full page fits in its account
+        assert!(8 + page.try_to_vec()?.len() <= PoolDirectory::SPACE);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    error::ProtocolError,
+    state::pool::*,
+    util::{MAX_ADMIN_LEN, MAX_CATEGORIES, MAX_NAME_LEN, MAX_POOL_DURATION, MIN_CLAIM_WINDOW, MIN_POOL_DURATION},
+};
+
+/// Room left in a series name for the round number, e.g. " #12"
+pub const ROUND_SUFFIX_LEN: usize = 12;
+
+/// A recurring program of rounds created from the same template.
+/// Each round is a regular `Pool`, spawned on schedule by a crank.
+/// Leftover matching from the previous round can be rolled into
+/// the current one.
+#[account]
+pub struct RoundSeries {
+    pub version: u8,
+    pub series_id: u64,
+    pub name: String,
+    /// Admins of the series, and of every round it spawns
+    pub admins: Vec<Pubkey>,
+    pub template: SeriesTemplate,
+    /// Earliest start for the next round
+    pub next_start: u64,
+    /// Rounds spawned so far
+    pub rounds: u32,
+    /// Stops spawning rounds once this many have been spawned
+    pub max_rounds: Option<u32>,
+    pub current_pool_id: Option<u64>,
+    pub previous_pool_id: Option<u64>,
+    pub bump: u8,
+}
+
+impl RoundSeries {
+    pub const SEED_PREFIX: &'static str = "series";
+
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 8                         // u64
+        + 4 + MAX_NAME_LEN          // String
+        + 4 + (32 * MAX_ADMIN_LEN)  // Vec<Pubkey>
+        + SeriesTemplate::SPACE     // SeriesTemplate
+        + 8                         // u64
+        + 4                         // u32
+        + 1 + 4                     // Option<u32>
+        + 1 + 8                     // Option<u64>
+        + 1 + 8                     // Option<u64>
+        + 1;                        // u8
+
+    pub fn new(
+        series_id: u64,
+        name: String,
+        admins: Vec<Pubkey>,
+        template: SeriesTemplate,
+        first_start: u64,
+        max_rounds: Option<u32>,
+        bump: u8,
+    ) -> Result<Self> {
+        Self::validate_name(&name)?;
+        require!(max_rounds != Some(0), ProtocolError::InvalidSeries);
+        template.validate()?;
+        Ok(Self {
+            version: Self::VERSION,
+            series_id,
+            name,
+            admins,
+            template,
+            next_start: first_start,
+            rounds: 0,
+            max_rounds,
+            current_pool_id: None,
+            previous_pool_id: None,
+            bump,
+        })
+    }
+
+    /// Names leave room for the round number
+    pub fn validate_name(name: &str) -> Result<()> {
+        require!(
+            name.as_bytes().len() <= MAX_NAME_LEN - ROUND_SUFFIX_LEN,
+            ProtocolError::NameTooLong
+        );
+        Ok(())
+    }
+
+    pub fn can_spawn(&self) -> Result<()> {
+        if let Some(max_rounds) = self.max_rounds {
+            require!(self.rounds < max_rounds, ProtocolError::SeriesFinished);
+        }
+        Ok(())
+    }
+
+    /// Returns the start and end of the next round. Slots that have
+    /// already passed, e.g. because the crank ran late, are skipped
+    /// so the series stays on its schedule.
+    pub fn next_round(&self, now: u64) -> (u64, u64) {
+        let mut start = self.next_start;
+        if start <= now {
+            let missed = (now - start) / self.template.interval + 1;
+            start += missed * self.template.interval;
+        }
+        (start, start + self.template.duration)
+    }
+
+    pub fn round_name(&self) -> String {
+        format!("{} #{}", self.name, self.rounds + 1)
+    }
+
+    /// Builds the next round from the template. Participants of the
+    /// previous round are carried over with `carry_over_project`.
+    pub fn build_pool(
+        &self,
+        pool_id: u64,
+        start: u64,
+        end: u64,
+        bump: u8,
+    ) -> Result<Pool> {
+        let template = &self.template;
+        let mut pool = Pool::new(
+            pool_id,
+            self.round_name(),
+            start,
+            end,
+            self.admins.clone(),
+            template.access.clone(),
+            template.caps.clone(),
+            bump,
+        )?;
+
+        for category in template.categories.iter() {
+            pool.add_category(category.clone())?;
+        }
+        pool.soft_close = template.soft_close.clone();
+
+        // Unclaimed payouts roll into the next round, once it exists
+        pool.unclaimed = template.claim_window.map(|window| UnclaimedPolicy {
+            window,
+            fate: UnclaimedFate::NextRound,
+        });
+
+        Ok(pool)
+    }
+
+    /// Records a newly spawned round as the current one
+    pub fn record_round(&mut self, pool_id: u64, start: u64) {
+        self.previous_pool_id = self.current_pool_id;
+        self.current_pool_id = Some(pool_id);
+        self.rounds += 1;
+        self.next_start = start + self.template.interval;
+    }
+}
+
+/// Settings every round in a series is created with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq)]
+pub struct SeriesTemplate {
+    pub access: PoolAccess,
+    pub caps: MatchingCaps,
+    pub categories: Vec<Category>,
+    pub soft_close: Option<SoftClose>,
+    /// Claim window for each round. Unclaimed payouts are
+    /// rolled over into the round that follows.
+    pub claim_window: Option<u64>,
+    /// Length of each round, in seconds
+    pub duration: u64,
+    /// Time between the starts of consecutive rounds, in seconds
+    pub interval: u64,
+    /// Lets the previous round's participants be carried into the next
+    pub carry_participants: bool,
+}
+
+impl SeriesTemplate {
+    pub const SPACE: usize = 1 + MatchingCaps::SPACE
+        + 4 + (Category::SPACE * MAX_CATEGORIES)
+        + 1 + SoftClose::SPACE
+        + 1 + 8
+        + 8
+        + 8
+        + 1;
+
+    pub fn validate(&self) -> Result<()> {
+        self.caps.validate()?;
+
+        require!(self.categories.len() <= MAX_CATEGORIES, ProtocolError::InvalidCategory);
+        for category in self.categories.iter() {
+            category.validate()?;
+        }
+        let allocated: f64 = self.categories.iter().map(|c| c.allocation).sum();
+        require!(allocated <= 1.0, ProtocolError::InvalidCategory);
+
+        if let Some(soft_close) = &self.soft_close {
+            soft_close.validate()?;
+        }
+        if let Some(window) = self.claim_window {
+            require!(window >= MIN_CLAIM_WINDOW, ProtocolError::InvalidUnclaimedPolicy);
+        }
+
+        require!(
+            self.duration >= MIN_POOL_DURATION && self.duration <= MAX_POOL_DURATION,
+            ProtocolError::PoolInvalidDuration
+        );
+        // Rounds can't overlap
+        require!(self.interval >= self.duration, ProtocolError::InvalidSeries);
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum UpdateSeriesField {
+    Name(String),
+    AddAdmin(Pubkey),
+    RemoveAdmin(Pubkey),
+    /// Applies to rounds spawned after the update
+    Template(SeriesTemplate),
+    MaxRounds(Option<u32>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monthly() -> SeriesTemplate {
+        SeriesTemplate {
+            access: PoolAccess::Open,
+            claim_window: Some(MIN_CLAIM_WINDOW),
+            duration: 21 * 86400,
+            interval: 30 * 86400,
+            carry_participants: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn try_series_schedule() -> Result<()> {
+        let mut series = RoundSeries::new(1, "Ecosystem".to_string(), vec![], monthly(), 1000, Some(2), 255)?;
+        let interval = series.template.interval;
+
+        // On time
+        let (start, end) = series.next_round(0);
+        assert_eq!((start, end), (1000, 1000 + 21 * 86400));
+        assert_eq!(series.round_name(), "Ecosystem #1");
+        series.record_round(7, start);
+        assert_eq!(series.current_pool_id, Some(7));
+        assert_eq!(series.next_start, 1000 + interval);
+
+        // The crank ran two and a half intervals late
+        let (start, _) = series.next_round(1000 + interval * 3 + interval / 2);
+        assert_eq!(start, 1000 + interval * 4);
+        series.record_round(8, start);
+        assert_eq!(series.previous_pool_id, Some(7));
+        assert_eq!(series.current_pool_id, Some(8));
+
+        assert!(series.can_spawn().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_series_template() -> Result<()> {
+        monthly().validate()?;
+
+        let overlapping = SeriesTemplate { interval: 86400, ..monthly() };
+        assert!(overlapping.validate().is_err());
+
+        let short_window = SeriesTemplate { claim_window: Some(60), ..monthly() };
+        assert!(short_window.validate().is_err());
+
+        let long_name = "a".repeat(MAX_NAME_LEN);
+        assert!(RoundSeries::new(1, long_name, vec![], monthly(), 1000, None, 255).is_err());
+
+        Ok(())
+    }
+}
@@ -1,37 +1,124 @@
 use anchor_lang::prelude::*;
 
-use crate::{error::ProtocolError, util::MAX_NAME_LEN};
+use crate::{
+    error::ProtocolError,
+    state::pool::{MintAmount, Participant},
+    util::{MAX_EARMARK_LEN, MAX_NAME_LEN, MAX_ORACLE_FEEDS},
+};
 
 #[account]
 pub struct FundingSource {
+    pub version: u8,
     pub name: String,
     pub authority: Pubkey,
     pub pool_id: u64,
-    pub amount: u64,
+    pub amount: u64, // Committed amount, caps what can be deposited
     pub bump: u8,
+    pub deposited: u64,
+    /// Unspent matching refunded or rolled over, in each mint
+    pub refunded: Vec<MintAmount>,
+    pub earmark: Earmark,
 }
 
 impl FundingSource {
     pub const SEED_PREFIX: &'static str = "source";
 
-    pub const SPACE: usize = 8 
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
         + 4 + MAX_NAME_LEN          // String
         + 32                        // Pubkey
         + 8                         // u64
         + 8                         // u64
         + 1                         // u8
-        + 8;
+        + 8                         // u64
+        + 4 + (MintAmount::SPACE * MAX_ORACLE_FEEDS) // Vec<MintAmount>
+        + 1 + 4 + (32 * MAX_EARMARK_LEN); // Enum (Vec<Pubkey>)
 
-    pub fn new(name: String, authority: Pubkey, pool_id: u64, amount: u64, bump: u8) -> Result<Self> {
+    pub fn new(name: String, authority: Pubkey, pool_id: u64, amount: u64, earmark: Earmark, bump: u8) -> Result<Self> {
         if name.as_bytes().len() > MAX_NAME_LEN {
             return Err(ProtocolError::NameTooLong.into());
         }
+        earmark.validate()?;
         Ok(Self {
+            version: Self::VERSION,
             name,
             authority,
             pool_id,
             amount,
             bump,
+            deposited: 0,
+            refunded: vec![],
+            earmark,
         })
     }
-}
\ No newline at end of file
+
+    /// Records a deposit into a pool, making sure the
+    /// source never deposits more than it committed.
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let deposited = self.deposited
+            .checked_add(amount)
+            .ok_or(ProtocolError::SourceCommitmentExceeded)?;
+
+        require!(deposited <= self.amount, ProtocolError::SourceCommitmentExceeded);
+
+        self.deposited = deposited;
+
+        Ok(())
+    }
+
+    /// Records a deposit refunded from a pool before it paid out
+    pub fn withdraw(&mut self, amount: u64) -> Result<()> {
+        self.deposited = self.deposited
+            .checked_sub(amount)
+            .ok_or(ProtocolError::AlgorithmFailure)?;
+
+        Ok(())
+    }
+
+    /// Unspent matching already refunded in `mint`
+    pub fn refunded_in(&self, mint: &Pubkey) -> u64 {
+        MintAmount::amount_in(&self.refunded, mint)
+    }
+
+    /// Records unspent matching refunded in `mint`
+    pub fn record_refund(&mut self, mint: Pubkey, amount: u64) -> Result<()> {
+        MintAmount::add(&mut self.refunded, mint, amount)
+    }
+}
+
+/// Restricts which participants a source's funds
+/// can be paid out to.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum Earmark {
+    Unrestricted,
+    Participants(Vec<Pubkey>),
+    Category(u8),
+}
+impl Default for Earmark {
+    fn default() -> Self {
+        Earmark::Unrestricted
+    }
+}
+
+impl Earmark {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Earmark::Unrestricted | Earmark::Category(_) => Ok(()),
+            Earmark::Participants(participants) => {
+                require!(participants.len() > 0, ProtocolError::InvalidEarmark);
+                require!(participants.len() <= MAX_EARMARK_LEN, ProtocolError::InvalidEarmark);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn is_eligible(&self, participant: &Participant) -> bool {
+        match self {
+            Earmark::Unrestricted => true,
+            Earmark::Participants(participants) => participants.contains(&participant.project_key),
+            Earmark::Category(category) => participant.category == Some(*category),
+        }
+    }
+}
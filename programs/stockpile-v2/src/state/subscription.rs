@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ProtocolError;
+
+#[account]
+#[derive(Default)]
+pub struct Subscription {
+    pub version: u8,
+    pub donor: Pubkey,
+    pub project: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64, // Per period, in the mint's base units
+    pub period: u64, // Seconds between payments
+    pub next_payment: u64,
+    pub payments: u64,
+    pub total_paid: u64,
+    pub status: SubscriptionStatus,
+    pub bump: u8,
+}
+
+impl Subscription {
+    pub const SEED_PREFIX: &'static str = "subscription";
+
+    /// Shortest period allowed between payments (1 day)
+    pub const MIN_PERIOD: u64 = 86400;
+
+    pub const VERSION: u8 = 1;
+
+    pub const SPACE: usize = 8
+        + 1                         // u8
+        + 32                        // Pubkey
+        + 32                        // Pubkey
+        + 32                        // Pubkey
+        + 8                         // u64
+        + 8                         // u64
+        + 8                         // u64
+        + 8                         // u64
+        + 8                         // u64
+        + 1                         // Enum (Singleton)
+        + 1                         // u8
+        + 64;                       // Padding
+
+    pub fn new(donor: Pubkey, project: Pubkey, mint: Pubkey, amount: u64, period: u64, bump: u8) -> Result<Self> {
+        require!(amount > 0, ProtocolError::InvalidSubscription);
+        require!(period >= Self::MIN_PERIOD, ProtocolError::InvalidSubscription);
+
+        // First payment is due immediately
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        Ok(Self {
+            version: Self::VERSION,
+            donor,
+            project,
+            mint,
+            amount,
+            period,
+            next_payment: current_time,
+            bump,
+            ..Default::default()
+        })
+    }
+
+    /// Checks if a payment can be executed, and schedules the next one.
+    /// Periods the crank missed are skipped rather than collected, so the
+    /// next payment is always a full period after this one.
+    pub fn record_payment(&mut self, now: u64) -> Result<()> {
+        match self.status {
+            SubscriptionStatus::Active => (),
+            SubscriptionStatus::Paused => return err!(ProtocolError::SubscriptionPaused),
+            SubscriptionStatus::Cancelled => return err!(ProtocolError::SubscriptionCancelled),
+        }
+
+        require!(now >= self.next_payment, ProtocolError::SubscriptionNotDue);
+
+        self.next_payment = self.next_payment.max(now) + self.period;
+        self.payments += 1;
+        self.total_paid += self.amount;
+
+        Ok(())
+    }
+
+    pub fn pause(&mut self) -> Result<()> {
+        require!(self.status == SubscriptionStatus::Active, ProtocolError::InvalidSubscription);
+        self.status = SubscriptionStatus::Paused;
+        Ok(())
+    }
+
+    /// Resumes a paused subscription. Periods missed while
+    /// paused are skipped rather than paid in a lump sum.
+    pub fn resume(&mut self, now: u64) -> Result<()> {
+        require!(self.status == SubscriptionStatus::Paused, ProtocolError::InvalidSubscription);
+
+        if self.next_payment < now {
+            self.next_payment = now;
+        }
+        self.status = SubscriptionStatus::Active;
+
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum SubscriptionStatus {
+    Active,
+    Paused,
+    Cancelled,
+}
+impl Default for SubscriptionStatus {
+    fn default() -> Self {
+        SubscriptionStatus::Active
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum UpdateSubscriptionField {
+    Pause,
+    Resume,
+    Amount(u64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_subscription_schedule() -> Result<()> {
+        let period = Subscription::MIN_PERIOD;
+        let mut subscription = Subscription {
+            amount: 100,
+            period,
+            next_payment: 1_000,
+            ..Default::default()
+        };
+
+        // Payments can't be made before they're due
+        assert!(subscription.record_payment(999).is_err());
+        subscription.record_payment(1_000)?;
+        assert_eq!(subscription.next_payment, 1_000 + period);
+        assert!(subscription.record_payment(1_000 + period - 1).is_err());
+
+        // A late crank collects one payment, not every missed period
+        let late = 1_000 + period * 5 + 10;
+        subscription.record_payment(late)?;
+        assert_eq!(subscription.next_payment, late + period);
+        assert!(subscription.record_payment(late + 1).is_err());
+        assert_eq!(subscription.payments, 2);
+        assert_eq!(subscription.total_paid, 200);
+
+        // Periods missed while paused are skipped
+        subscription.pause()?;
+        assert!(subscription.record_payment(late + period).is_err());
+        let resumed = late + period * 3;
+        subscription.resume(resumed)?;
+        assert_eq!(subscription.next_payment, resumed);
+        subscription.record_payment(resumed)?;
+        assert_eq!(subscription.next_payment, resumed + period);
+        assert!(subscription.resume(resumed).is_err());
+
+        Ok(())
+    }
+}
@@ -1,40 +1,60 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, spl_token_2022, Mint};
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig,
+    BaseStateWithExtensions,
+    ExtensionType,
+    StateWithExtensions,
+};
+use spl_token_2022::pod::pod_from_bytes;
 use std::str::FromStr;
 
 use crate::error::ProtocolError;
+use crate::state::{config::{Fees, RestrictedExtension}, pool::Pool};
 
 pub const MAX_NAME_LEN: usize = 100;
+pub const MAX_URI_LEN: usize = 200;
 pub const MAX_ADMIN_LEN: usize = 4;
+pub const MAX_EARMARK_LEN: usize = 10;
+pub const MAX_CATEGORIES: usize = 5;
+pub const MAX_ORACLE_FEEDS: usize = 4;
+pub const MAX_ORACLE_SOURCES: usize = 3;
+pub const MAX_PROJECT_MINTS: usize = 4;
+pub const MAX_POOL_TRANSITIONS: usize = 5;
+pub const MIN_POOL_DURATION: u64 = 86400; // 1 day
+pub const MAX_POOL_DURATION: u64 = 7776000; // 90 days
+pub const MIN_CLAIM_WINDOW: u64 = 604800; // 7 days
+pub const MAX_FEE_BPS: u16 = 1000; // 10%
+pub const MAX_FEE_EXEMPT: usize = 10;
+pub const MAX_DIRECTORY_PAGE_LEN: usize = 64;
+pub const MAX_BATCH_LEN: usize = 10;
+pub const BPS_DENOMINATOR: u64 = 10000;
+
+// Token-2022 extension types newer than the token library we build against
+pub const TRANSFER_HOOK_EXTENSION: u16 = 14;
+pub const PAUSABLE_EXTENSION: u16 = 26;
 
 pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
 pub const USDC_DEVNET_MINT: &str = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU";
+pub const USDC_DECIMALS: u8 = 6;
+pub const USD_DECIMALS: u8 = 6;
 
 pub const SOL_USD_PRICE_FEED_ID: &str = "ALP8SdU9oARYVLgLR7LrqMNCYBnhtnQz1cj6bwgwQmgj";
 // THIS IS MAINNET
 pub const USDC_USD_PRICE_FEED_ID: &str = "Gnt27xtC473ZT2Mw5u8wZ68Z3gULkSTb5DuxJy7eJotD";
 
-pub const SUPPORTED_SPL_MINTS: [&'static str; 2] = [USDC_MINT, USDC_DEVNET_MINT];
 
 pub fn to_pubkey(string: &str) -> Pubkey {
     Pubkey::from_str(&string).expect("Error parsing public key from string.")
 }
 
-pub fn mint_is_supported(mint_pubkey: &Pubkey) -> Result<()> {
-    for suppported_mint in SUPPORTED_SPL_MINTS {
-        if to_pubkey(suppported_mint).eq(mint_pubkey) {
-            return Ok(());
-        }
-    }
-    Err(ProtocolError::MintNotSupported.into())
-}
-
 pub fn set_and_maybe_realloc<'info, T>(
     account: &mut Account<'info, T>,
     new_data: &T,
     payer: AccountInfo<'info>,
     system_program: AccountInfo<'info>,
-) -> Result<()>
+) -> Result<u64>
 where
     T: AccountDeserialize
         + AccountSerialize
@@ -44,14 +64,67 @@ where
 {
     let account_info = account.to_account_info();
 
-    // See if it needs to be reallocated
-    let new_account_size = (new_data.try_to_vec()?).len();
-    if new_account_size > account_info.data_len() {
-        // Determine additional rent required
-        let lamports_required = (Rent::get()?).minimum_balance(new_account_size);
-        let additional_rent_to_fund = lamports_required - account_info.lamports();
+    // See if it needs to be reallocated, leaving room for the discriminator
+    let new_account_size = 8 + (new_data.try_to_vec()?).len();
+    let rent = maybe_realloc(&account_info, new_account_size, payer, system_program)?;
+
+    //account.set_inner(new_data);
+    Ok(rent)
+}
+
+/// Sets a pool's data, growing the account if needed. Any rent the
+/// payer funds is credited to the pool's creator, and returned to them
+/// when the pool closes, so closing never needs more than their account.
+pub fn set_pool_and_maybe_realloc<'info>(
+    account: &mut Account<'info, Pool>,
+    mut new_data: Pool,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    let rent_payer = new_data.rent_payers
+        .first()
+        .map(|r| r.payer)
+        .unwrap_or(payer.key());
+    let account_info = account.to_account_info();
+
+    // Transitions are written in place, so always leave room
+    // for the ones the pool hasn't made yet
+    let size = |data: &Pool| -> Result<usize> {
+        Ok(8 + data.try_to_vec()?.len() + data.transition_room())
+    };
+
+    // Reserve the rent entry before the account is sized
+    if size(&new_data)? > account_info.data_len() {
+        new_data.record_rent(rent_payer, 0);
+    }
+
+    let rent = maybe_realloc(&account_info, size(&new_data)?, payer, system_program)?;
+    if rent > 0 {
+        new_data.record_rent(rent_payer, rent);
+    }
+
+    account.set_inner(new_data);
+    Ok(())
+}
+
+/// Grows an account to `new_size` if it's smaller, funding the
+/// additional rent from the payer. Returns the rent paid.
+pub fn maybe_realloc<'info>(
+    account_info: &AccountInfo<'info>,
+    new_size: usize,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<u64> {
+    if new_size <= account_info.data_len() {
+        return Ok(0);
+    }
+
+    // Determine additional rent required
+    let lamports_required = (Rent::get()?).minimum_balance(new_size);
+    let additional_rent_to_fund = lamports_required.saturating_sub(account_info.lamports());
 
-        // Perform transfer of additional rent
+    // Perform transfer of additional rent
+    if additional_rent_to_fund > 0 {
         system_program::transfer(
             CpiContext::new(
                 system_program,
@@ -62,10 +135,123 @@ where
             ),
             additional_rent_to_fund,
         )?;
+    }
+
+    account_info.realloc(new_size, false)?;
+
+    Ok(additional_rent_to_fund)
+}
+
+/// Moves tokens with `transfer_checked`, which both the original token
+/// program and Token-2022 support. Returns the amount that arrived,
+/// which is less than `amount` if the mint charges a transfer fee.
+pub fn transfer_tokens<'info>(
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    let fee = transfer_fee_on(&mint_info.try_borrow_data()?, amount, Clock::get()?.epoch)?;
 
-        // Serialize new data
-        account_info.realloc(new_account_size, false)?;
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program,
+            token_interface::TransferChecked {
+                from,
+                mint: mint_info,
+                to,
+                authority,
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )?;
+
+    Ok(amount - fee)
+}
+
+/// The fee a Token-2022 mint withholds from a transfer of `amount`.
+/// Mints without the transfer fee extension charge nothing.
+pub fn transfer_fee_on(mint_data: &[u8], amount: u64, epoch: u64) -> Result<u64> {
+    let transfer_fee_config = u16::from(ExtensionType::TransferFeeConfig);
+    match mint_extensions(mint_data)?.into_iter().find(|(t, _)| *t == transfer_fee_config) {
+        Some((_, value)) => pod_from_bytes::<TransferFeeConfig>(value)?
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or_else(|| ProtocolError::AlgorithmFailure.into()),
+        None => Ok(0),
     }
-    //account.set_inner(new_data);
+}
+
+/// Returns the `RestrictedExtension`s a mint has, as a bitmask.
+/// Mints owned by the original token program have none.
+pub fn restricted_extensions(mint_data: &[u8]) -> Result<u32> {
+    Ok(mint_extensions(mint_data)?
+        .into_iter()
+        .filter_map(|(extension_type, _)| RestrictedExtension::from_extension_type(extension_type))
+        .fold(0, |bits, extension| bits | extension.bit()))
+}
+
+/// A mint's extensions, as raw types and their values. The TLV data
+/// is read directly, since the token library fails on extension
+/// types newer than itself.
+fn mint_extensions(mint_data: &[u8]) -> Result<Vec<(u16, &[u8])>> {
+    // The TLV data runs to the end of the account
+    let tlv_len = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(mint_data)?
+        .get_tlv_data()
+        .len();
+    let mut tlv_data = &mint_data[mint_data.len() - tlv_len..];
+    let mut extensions = vec![];
+    while tlv_data.len() >= 4 {
+        let extension_type = u16::from_le_bytes([tlv_data[0], tlv_data[1]]);
+        if extension_type == u16::from(ExtensionType::Uninitialized) {
+            break;
+        }
+        let length = u16::from_le_bytes([tlv_data[2], tlv_data[3]]) as usize;
+        let value = tlv_data
+            .get(4..4 + length)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        extensions.push((extension_type, value));
+        tlv_data = &tlv_data[4 + length..];
+    }
+    Ok(extensions)
+}
+
+/// Pays the fees on a transfer out of `from`. The treasury
+/// account is only required when there's a protocol fee.
+pub fn transfer_fees<'info>(
+    fees: &Fees,
+    from: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    treasury_token_account: Option<AccountInfo<'info>>,
+    frontend_token_account: Option<AccountInfo<'info>>,
+    token_program: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let pay = |to: AccountInfo<'info>, amount: u64| transfer_tokens(
+        from.clone(),
+        to,
+        authority.clone(),
+        mint,
+        token_program.clone(),
+        signer_seeds,
+        amount,
+    );
+
+    if fees.protocol > 0 {
+        let treasury = treasury_token_account.ok_or(ProtocolError::TreasuryAccountMissing)?;
+        pay(treasury, fees.protocol)?;
+    }
+    if let Some(frontend) = frontend_token_account {
+        if fees.frontend > 0 {
+            pay(frontend, fees.frontend)?;
+        }
+    }
+
     Ok(())
-}
\ No newline at end of file
+}